@@ -1,9 +1,29 @@
-use std::{path::Path, time::{Duration, Instant}};
+use std::{io::{stdout, Write}, path::Path, time::{Duration, Instant}};
 
 use ratatui::widgets::ListState;
-use crate::todo::TodoList;
+use crate::archive::Archive;
+use crate::backup::{self, Backup};
+use crate::fields::{FieldDef, FieldFilter, FieldValue};
+use crate::estimate::Estimate;
+use crate::history::{History, Snapshot};
+use crate::links::{Hyperlink, Link};
+use crate::paths::DataPaths;
+use crate::board::{self, Column, Grouping};
+use crate::pomodoro::{Phase, Pomodoro, PomodoroSettings};
+use crate::recovery::{self, Choice, Recovery};
+use crate::recurrence::Recurrence;
+use crate::status::Status;
+use crate::timer::Timer;
+use crate::todo::{parse_due_date, parse_tags, parse_todo_ids, Filter, IdCounter, SortOrder, TodoId, TodoItem, TodoList};
+use crate::workspace::Workspace;
 
 const MAX_CHARS: usize = 180;
+// changes are saved once nothing else changed for this long
+const AUTOSAVE_DELAY: Duration = Duration::from_secs(1);
+// after a failed autosave, so a full disk doesn't ring the bell every second
+const AUTOSAVE_RETRY: Duration = Duration::from_secs(30);
+// besides the one taken on start, saving backs up the data file it writes over at most this often
+const BACKUP_INTERVAL: Duration = Duration::from_secs(15 * 60);
 
 #[derive(PartialEq, Debug)]
 pub enum State {
@@ -12,13 +32,128 @@ pub enum State {
 	Exit,
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone, Copy)]
 pub enum InputMode {
 	Visual,
 	Input,
 	Select,
 	Popup,
 	PopupInput,
+	Prompt(Prompt),
+	// browsing the archive, typing searches it
+	Archive,
+	Trash,
+	// browsing the backups of the data file
+	Backups,
+	// the data file could not be read, picking what to continue with
+	Recovery,
+	Confirm(Confirm),
+}
+
+// single line inputs asked about the selected todo, typed in the Input box
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Prompt {
+	DueDate,
+	Tags,
+	TagFilter,
+	Recurrence,
+	NewList,
+	RenameList,
+	DeleteList,
+	MoveToList,
+	BlockedBy,
+	PomodoroSettings,
+	ArchiveAfter,
+	TrashRetention,
+	Fields,
+	EditField,
+	FieldFilter,
+	BackupCount,
+	Estimate,
+	Note,
+}
+
+impl Prompt {
+	pub fn title(&self) -> &'static str {
+		match self {
+			Prompt::DueDate => "Due date (dd-mm-YYYY [HH:MM], today, tomorrow, +Nd; empty clears)",
+			Prompt::Tags => "Tags (separated by spaces or commas; empty clears)",
+			Prompt::TagFilter => "Show todos tagged with any of (empty shows all)",
+			Prompt::Recurrence => "Repeat (daily, weekly mon,thu, monthly 15, every N days after completion; empty clears)",
+			Prompt::NewList => "Name of the new list",
+			Prompt::RenameList => "New name of this list",
+			Prompt::DeleteList => "Type the name of this list to delete it, its todos go to the trash",
+			Prompt::MoveToList => "Move the selected todo to list",
+			Prompt::BlockedBy => "Blocked by todos (ids like #3 #5; empty clears)",
+			Prompt::PomodoroSettings => "Pomodoro minutes as focus/short break/long break/sessions before a long break",
+			Prompt::ArchiveAfter => "Archive done and cancelled todos after this many days (empty turns it off)",
+			Prompt::TrashRetention => "Keep deleted todos in the trash for this many days",
+			Prompt::BackupCount => "Keep this many backups of the data file, one is taken on start and every 15 minutes while saving",
+			Prompt::Fields => "Fields of this list as name:kind with kinds text, number, date or bool; * shows it as a column (e.g. ticket:text* estimate:number)",
+			Prompt::EditField => "Set a field as name = value, no value clears it",
+			Prompt::Note => "New note on this todo, the body stays as it is",
+			Prompt::Estimate => "Estimate as time (45m, 1h30m, 2h) or story points (3p); empty clears",
+			Prompt::FieldFilter => "Show todos whose field matches (estimate>3, customer=acme, shipped=no, or a name for any value; empty shows all)",
+		}
+	}
+
+	// what submitting the prompt does to the lists, for the undo history. None when it changes no list
+	pub fn change_description(&self) -> Option<&'static str> {
+		match self {
+			Prompt::DueDate => Some("set the due date of"),
+			Prompt::Tags => Some("set the tags of"),
+			Prompt::Recurrence => Some("set the repeat of"),
+			Prompt::BlockedBy => Some("set the blockers of"),
+			Prompt::MoveToList => Some("move"),
+			Prompt::EditField => Some("set a field of"),
+			Prompt::Estimate => Some("estimate"),
+			Prompt::Note => Some("add a note to"),
+			Prompt::Fields => Some("change the fields of list"),
+			Prompt::NewList => Some("add list"),
+			Prompt::RenameList => Some("rename list"),
+			Prompt::DeleteList => Some("delete list"),
+			Prompt::TagFilter | Prompt::PomodoroSettings | Prompt::ArchiveAfter | Prompt::TrashRetention | Prompt::BackupCount | Prompt::FieldFilter => None,
+		}
+	}
+
+	pub fn needs_todo(&self) -> bool {
+		matches!(self, Prompt::DueDate | Prompt::Tags | Prompt::Recurrence | Prompt::MoveToList | Prompt::BlockedBy | Prompt::EditField | Prompt::Estimate | Prompt::Note)
+	}
+}
+
+// destructive actions wait in a popup until they are confirmed
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum Confirm {
+	DeleteTodo(TodoId),
+	// index into the trash
+	PurgeTrashed(usize),
+	EmptyTrash,
+	// index into the listed backups
+	RestoreBackup(usize),
+}
+
+// the todo whose clock is running, with a stopwatch for the current session
+#[derive(Debug)]
+pub struct Tracking {
+	pub list: usize,
+	pub todo: TodoId,
+	pub timer: Timer,
+}
+
+#[derive(Debug)]
+pub struct Notice {
+	pub message: String,
+	pub shown_at: Instant,
+	// alerts are highlighted and came with a bell
+	pub alert: bool,
+}
+
+// the same list and selection, shown as rows or as cards in columns
+#[derive(PartialEq, Debug, Default, Clone, Copy)]
+pub enum View {
+	#[default]
+	List,
+	Board,
 }
 
 #[derive(Debug)]
@@ -28,51 +163,264 @@ pub struct App {
 	pub should_quit: bool,
 	pub cursor_index: usize,
 	pub input_mode: InputMode,
+	pub prompt_return_mode: InputMode,
+	pub confirm_return_mode: InputMode,
 	pub input: String,
-	pub todo_list: TodoList,
+	pub workspace: Workspace,
 	pub todo_list_state: ListState,
-	pub todo_list_index: usize,
+	pub selected_todo: Option<TodoId>,
+	pub new_todo_parent: Option<TodoId>,
+	pub sort_order: SortOrder,
+	pub filter: Filter,
 	pub show_todo_popup: bool,
+	pub show_todo_history: bool,
+	// index in the links of the todo shown in the popup
+	pub selected_link: usize,
+	// a file the main loop opens in $EDITOR, it owns the terminal
+	pub editor_request: Option<Link>,
+	// links of the last frame, written over it as terminal hyperlinks
+	pub hyperlinks: Vec<Hyperlink>,
+	pub show_keys: bool,
+	pub show_report: bool,
+	pub view: View,
+	pub board_grouping: Grouping,
+	// a todo with several tags has a card in several columns, this is the one that is selected
+	pub board_column: usize,
+	pub notice: Option<Notice>,
+	pub tracking: Option<Tracking>,
+	pub pomodoro: Option<Pomodoro>,
 	pub popup_input: String,
+	pub archive: Archive,
+	pub archive_state: ListState,
+	pub trash_state: ListState,
+	// newest first, listed when the backups are opened
+	pub backups: Vec<Backup>,
+	pub backup_state: ListState,
+	// set until the user picks what to continue with, saving meanwhile puts the broken file back
+	pub recovery: Option<Recovery>,
+	pub recovery_state: ListState,
+	pub history: History,
+	pub paths: DataPaths,
+	// set by every change that is saved and cleared once the files are written. selecting and scrolling
+	// don't set it, they are written along with the next change
+	unsaved: bool,
+	// when the last change was made, or the last autosave failed
+	changed_at: Instant,
+	// when the data file was last backed up, or tried to be
+	backed_up_at: Instant,
 }
 
 impl App {
-	pub fn new() -> Self {
-		let todo_list = Self::load_or_create_todo_list();
-		App {
+	// fails when the data file is from a newer version, starting empty would overwrite it on save
+	pub fn new(paths: DataPaths) -> std::io::Result<Self> {
+		// before the data file, which may be moved aside for recovery and should not stay there when these fail
+		let mut notices = Vec::new();
+		let mut archive = Self::load_or_create_archive(paths.archive_file(), &mut notices)?;
+		let history = Self::load_or_create_history(paths.history_file(), &mut notices)?;
+		let (mut workspace, recovery) = Self::load_or_create_workspace(&paths, &mut notices)?;
+		workspace.adopt_archive(&mut archive);
+		let mut app = App {
 			state: State::Startup,
 			start_time: Instant::now(),
 			should_quit: false,
 			cursor_index: 0,
-			input_mode: InputMode::Visual,
+			input_mode: if recovery.is_some() { InputMode::Recovery } else { InputMode::Visual },
+			prompt_return_mode: InputMode::Visual,
+			confirm_return_mode: InputMode::Visual,
 			input: String::new(),
-			todo_list,						// USING SHORTHAND FOR THIS ONE ONLY IN THIS CASE, can be confusing
 			todo_list_state: ListState::default(),
-			todo_list_index: 0,
+			selected_todo: workspace.active_list().selected(),
+			new_todo_parent: None,
+			sort_order: workspace.active_list().sort_order(),
+			workspace,						// USING SHORTHAND FOR THIS ONE ONLY IN THIS CASE, can be confusing
+			filter: Filter::All,
 			show_todo_popup: false,
+			show_todo_history: false,
+			selected_link: 0,
+			editor_request: None,
+			hyperlinks: Vec::new(),
+			show_keys: false,
+			show_report: false,
+			view: View::List,
+			board_grouping: Grouping::Status,
+			board_column: 0,
+			notice: None,
+			tracking: None,
+			pomodoro: None,
 			popup_input: String::new(),
+			archive,
+			archive_state: ListState::default(),
+			trash_state: ListState::default(),
+			backups: Vec::new(),
+			backup_state: ListState::default(),
+			recovery_state: ListState::default().with_selected(recovery.as_ref().map(|_| 0)),
+			recovery,
+			history,
+			paths,
+			unsaved: false,
+			changed_at: Instant::now(),
+			backed_up_at: Instant::now(),
+		};
+		app.sync_tracking();
+		app.auto_archive();
+		if !notices.is_empty() {
+			app.alert(notices.join(". "));
 		}
+		Ok(app)
 	}
 
-	fn load_or_create_todo_list() -> TodoList {
-		let filename = ".todo_temp.json";
-		let exists = Path::try_exists(Path::new(filename));
-		match exists {
-			Ok(true) => {
-				match TodoList::load_from_file(filename) {
-                    Ok(loaded_list) => loaded_list,
-					Err(e) => {println!("Error loading from file {}", e); TodoList::new()}
+	// a file that loads is backed up as it was found. one whose contents are broken is moved aside for the
+	// recovery screen, any other error stops the start so an empty list never replaces a file that is there
+	fn load_or_create_workspace(paths: &DataPaths, notices: &mut Vec<String>) -> std::io::Result<(Workspace, Option<Recovery>)> {
+		let filename = paths.data_file();
+		if !Path::try_exists(filename)? {
+			return Ok((Workspace::new(), None));
+		}
+		match Workspace::load_from_file(filename) {
+			Ok(loaded_workspace) => {
+				if let Err(e) = backup::create(paths, loaded_workspace.settings().backup_count as usize) {
+					notices.push(format!("The todos could not be backed up: {}", e));
 				}
+				Ok((loaded_workspace, None))
 			}
-			Ok(false) => {
-				// println!("File not found");
-				TodoList::new()
+			Err(e) if recovery::is_corrupt(&e) => Ok((Workspace::new(), Some(Recovery::start(paths, &e)?))),
+			Err(e) => Err(e),
+		}
+	}
+
+	// a file that does not parse is moved aside so that saving can't write over it, any other error stops the start
+	fn load_or_create_archive(filename: &Path, notices: &mut Vec<String>) -> std::io::Result<Archive> {
+		match Archive::load_from_file(filename) {
+			Ok(archive) => Ok(archive),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Archive::new()),
+			Err(e) if recovery::is_corrupt(&e) => {
+				let moved_to = recovery::move_aside(filename)?;
+				notices.push(format!("The archive could not be read ({}), it was moved to {}", e, moved_to.display()));
+				Ok(Archive::new())
 			}
-			Err(e) => {
-				println!("Error checking for file existence");
-				TodoList::new()
+			Err(e) => Err(e),
+		}
+	}
+
+	fn load_or_create_history(filename: &Path, notices: &mut Vec<String>) -> std::io::Result<History> {
+		match History::load_from_file(filename) {
+			Ok(history) => Ok(history),
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(History::new()),
+			Err(e) if recovery::is_corrupt(&e) => {
+				let moved_to = recovery::move_aside(filename)?;
+				notices.push(format!("The undo history could not be read ({}), it was moved to {}", e, moved_to.display()));
+				Ok(History::new())
+			}
+			Err(e) => Err(e),
+		}
+	}
+
+	pub fn save(&mut self) -> std::io::Result<()> {
+		// the empty workspace shown behind the recovery screen is not the user's data
+		if let Some(recovery) = &self.recovery {
+			return recovery.put_back(&self.paths);
+		}
+		self.store_list_view();
+		// a failed backup is reported but does not keep the changes from being saved
+		if self.backed_up_at.elapsed() >= BACKUP_INTERVAL {
+			self.backed_up_at = Instant::now();
+			if let Err(e) = backup::create(&self.paths, self.workspace.settings().backup_count as usize) {
+				self.alert(format!("Could not back up the todos: {}", e));
 			}
 		}
+		self.workspace.save_to_file(self.paths.data_file())?;
+		self.archive.save_to_file(self.paths.archive_file())?;
+		self.history.save_to_file(self.paths.history_file())?;
+		self.unsaved = false;
+		Ok(())
+	}
+
+	// each change pushes the autosave back
+	fn mark_changed(&mut self) {
+		self.unsaved = true;
+		self.changed_at = Instant::now();
+	}
+
+	pub fn has_unsaved_changes(&self) -> bool {
+		self.unsaved
+	}
+
+	fn autosave(&mut self) {
+		if !self.has_unsaved_changes() || self.changed_at.elapsed() < AUTOSAVE_DELAY {
+			return;
+		}
+		if let Err(e) = self.save() {
+			self.changed_at = Instant::now() + AUTOSAVE_RETRY - AUTOSAVE_DELAY;
+			self.alert(format!("Could not save the todos: {}", e));
+		}
+	}
+
+	fn snapshot(&mut self, description: String) -> Snapshot {
+		self.snapshot_of(description, false)
+	}
+
+	// for changes that move todos in or out of the archive, the others leave it out to keep the history small
+	fn archive_snapshot(&mut self, description: String) -> Snapshot {
+		self.snapshot_of(description, true)
+	}
+
+	fn snapshot_of(&mut self, description: String, with_archive: bool) -> Snapshot {
+		self.store_list_view();
+		Snapshot::new(description, &self.workspace, with_archive.then_some(&self.archive))
+	}
+
+	// keeps the state from before a change so it can be undone
+	fn checkpoint(&mut self, description: String) {
+		let before = self.snapshot(description);
+		self.record(before);
+	}
+
+	fn record(&mut self, before: Snapshot) {
+		self.history.record(before);
+		self.mark_changed();
+	}
+
+	fn selected_title(&self) -> String {
+		self.selected_todo
+			.and_then(|id| self.workspace.active_list().get_todo(id))
+			.map(|todo| todo.title().to_string())
+			.unwrap_or_default()
+	}
+
+	pub fn undo(&mut self) {
+		let with_archive = self.history.next_undo().is_some_and(Snapshot::has_archive);
+		let current = self.snapshot_of(String::new(), with_archive);
+		match self.history.undo(current) {
+			Some(previous) => {
+				let message = format!("Undid: {}", previous.description());
+				self.restore_snapshot(previous);
+				self.notify(message);
+			}
+			None => self.notify(String::from("Nothing to undo")),
+		}
+	}
+
+	pub fn redo(&mut self) {
+		let with_archive = self.history.next_redo().is_some_and(Snapshot::has_archive);
+		let current = self.snapshot_of(String::new(), with_archive);
+		match self.history.redo(current) {
+			Some(next) => {
+				let message = format!("Redid: {}", next.description());
+				self.restore_snapshot(next);
+				self.notify(message);
+			}
+			None => self.notify(String::from("Nothing to redo")),
+		}
+	}
+
+	fn restore_snapshot(&mut self, snapshot: Snapshot) {
+		snapshot.restore(&mut self.workspace, &mut self.archive);
+		self.mark_changed();
+		self.load_list_view();
+		self.sync_tracking();
+		if self.selected_todo.is_none() && self.input_mode == InputMode::Select {
+			self.input_mode = InputMode::Visual;
+		}
 	}
 
 	pub fn update(&mut self) {
@@ -81,6 +429,8 @@ impl App {
 				self.state = State::Display;
 			}
 		}
+		self.update_pomodoro();
+		self.autosave();
 	}
 
 	pub fn move_cursor_left(&mut self) {
@@ -97,7 +447,7 @@ impl App {
         let index = self.byte_index();
 		if index < MAX_CHARS {
 			match self.input_mode {
-				InputMode::Input => {
+				InputMode::Input | InputMode::Prompt(_) | InputMode::Archive => {
 					self.input.insert(index, new_char);
 					self.move_cursor_right();
 				}
@@ -112,7 +462,7 @@ impl App {
 	
 	pub fn byte_index(&mut self) -> usize {
 		match self.input_mode {
-			InputMode::Input => {
+			InputMode::Input | InputMode::Prompt(_) | InputMode::Archive => {
 				self.input
 					.char_indices()
 					.map(|(i, _)| i)
@@ -131,7 +481,7 @@ impl App {
     }
 
 	pub fn clamp_cursor(&self, new_cursor_pos: usize) -> usize {
-		if self.input_mode == InputMode::Input {
+		if matches!(self.input_mode, InputMode::Input | InputMode::Prompt(_) | InputMode::Archive) {
 			new_cursor_pos.clamp(0, self.input.chars().count())
 		}
 		else if self.input_mode == InputMode::PopupInput {
@@ -141,7 +491,7 @@ impl App {
 	}
 
 	pub fn clamp_todo_list_index(&self, idx: usize) -> usize {
-		idx.min(self.todo_list.len().saturating_sub(1))
+		idx.min(self.visible_todos().len().saturating_sub(1))
 	}
 
 	pub fn delete_char(&mut self) {
@@ -149,7 +499,7 @@ impl App {
 		if is_not_cursor_leftmost {
 			let current_index = self.cursor_index;
 			let from_left_to_current_index = current_index - 1;
-			if self.input_mode == InputMode::Input {
+			if matches!(self.input_mode, InputMode::Input | InputMode::Prompt(_) | InputMode::Archive) {
 				let before_char_to_delete = self.input.chars().take(from_left_to_current_index);
 				let after_char_to_delete = self.input.chars().skip(current_index);
 				self.input = before_char_to_delete.chain(after_char_to_delete).collect();
@@ -161,7 +511,6 @@ impl App {
 				self.popup_input = before_char_to_delete.chain(after_char_to_delete).collect();
 				self.move_cursor_left();
 			}
-			else {}
 		}
 	}
 
@@ -169,14 +518,73 @@ impl App {
 		self.cursor_index = 0;
 	}
 
+	pub fn is_new_todo_title_taken(&self) -> bool {
+		match self.new_todo_parent {
+			Some(parent) => self.workspace.active_list().contains_subtask_title(parent, &self.input),
+			None => self.workspace.active_list().contains_title(&self.input),
+		}
+	}
+
 	pub fn submit_new_todo(&mut self) {
-		self.todo_list.add_todo(self.input.clone(), None);
+		self.checkpoint(format!("add '{}'", self.input));
+		let (list, ids) = self.workspace.active_list_and_ids();
+		match self.new_todo_parent.take() {
+			Some(parent) => {
+				list.add_subtask(ids, parent, self.input.clone());
+				self.input_mode = InputMode::Select;
+			}
+			None => {
+				list.add_todo(ids, self.input.clone(), None);
+				self.input_mode = InputMode::Visual;
+			}
+		}
+	}
+
+	pub fn start_subtask(&mut self) {
+		if self.selected_todo.is_some() {
+			self.new_todo_parent = self.selected_todo;
+			self.input_mode = InputMode::Input;
+		}
+	}
+
+	pub fn cancel_new_todo(&mut self) {
+		self.input_mode = if self.new_todo_parent.take().is_some() { InputMode::Select } else { InputMode::Visual };
+		self.input.clear();
+		self.reset_cursor();
+	}
+
+	pub fn visible_todos(&self) -> Vec<TodoId> {
+		self.workspace.active_list().visible_ids(&self.filter)
+	}
+
+	// index of the selected todo among the visible ones
+	pub fn selected_index(&self) -> Option<usize> {
+		let id = self.selected_todo?;
+		self.visible_todos().iter().position(|&visible| visible == id)
+	}
+
+	pub fn select_todo_at(&mut self, idx: usize) {
+		let visible = self.visible_todos();
+		self.selected_todo = visible.get(self.clamp_todo_list_index(idx)).copied();
+		self.sync_todo_list_state();
+	}
+
+	// the highlighted row follows the selected id, so sorting or removing items never moves the selection to another todo.
+	// if the selected todo got filtered out the first visible one is selected instead
+	pub fn sync_todo_list_state(&mut self) {
+		let mut idx = self.selected_index();
+		if idx.is_none() && self.selected_todo.is_some() {
+			let visible = self.visible_todos();
+			self.selected_todo = visible.first().copied();
+			idx = self.selected_todo.map(|_| 0);
+		}
+		self.todo_list_state.select(idx);
 	}
 
 	pub fn next_todo(&mut self) {
-		let i = match self.todo_list_state.selected() {
+		let i = match self.selected_index() {
 			Some(i) => {
-				if i >= self.todo_list.len() - 1 {
+				if i + 1 >= self.visible_todos().len() {
 					0
 				} else {
 					i + 1
@@ -184,22 +592,967 @@ impl App {
 			}
 			None => 0,
 		};
-		self.todo_list_state.select(Some(i));
-		self.todo_list_index = i;
+		self.select_todo_at(i);
 	}
 
 	pub fn previous_todo(&mut self) {
-		let i = match self.todo_list_state.selected() {
+		let i = match self.selected_index() {
 			Some(i) => {
 				if i == 0 {
-					self.todo_list.len() - 1
+					self.visible_todos().len().saturating_sub(1)
 				} else {
 					i - 1
 				}
 			}
 			None => 0,
 		};
-		self.todo_list_state.select(Some(i));
-		self.todo_list_index = i;
+		self.select_todo_at(i);
+	}
+
+	pub fn toggle_view(&mut self) {
+		self.view = match self.view {
+			View::List => View::Board,
+			View::Board => View::List,
+		};
+	}
+
+	pub fn cycle_board_grouping(&mut self) {
+		self.board_grouping = self.board_grouping.next();
+	}
+
+	pub fn board_columns(&self) -> Vec<Column> {
+		board::columns(self.workspace.active_list(), &self.filter, self.board_grouping)
+	}
+
+	// column and row of the selected card, preferring the column it was selected in
+	pub fn selected_card(&self, columns: &[Column]) -> Option<(usize, usize)> {
+		let id = self.selected_todo?;
+		let find = |col: usize| columns.get(col)?.todos.iter().position(|&todo| todo == id).map(|row| (col, row));
+		find(self.board_column).or_else(|| (0..columns.len()).find_map(find))
+	}
+
+	// up and down inside the column of the selected card
+	pub fn move_board_selection(&mut self, down: bool) {
+		let columns = self.board_columns();
+		let Some((col, row)) = self.selected_card(&columns) else {
+			return;
+		};
+		let todos = &columns[col].todos;
+		let row = match down {
+			true => (row + 1) % todos.len(),
+			false => row.checked_sub(1).unwrap_or(todos.len() - 1),
+		};
+		self.board_column = col;
+		self.selected_todo = Some(todos[row]);
+	}
+
+	// left and right to the closest column that has cards, keeping about the same row
+	pub fn switch_board_column(&mut self, right: bool) {
+		let columns = self.board_columns();
+		let Some((col, row)) = self.selected_card(&columns) else {
+			return;
+		};
+		let target = match right {
+			true => columns.iter().enumerate().skip(col + 1).find(|(_, column)| !column.todos.is_empty()),
+			false => columns.iter().enumerate().take(col).rev().find(|(_, column)| !column.todos.is_empty()),
+		};
+		if let Some((target, column)) = target {
+			self.board_column = target;
+			self.selected_todo = Some(column.todos[row.min(column.todos.len() - 1)]);
+		}
+	}
+
+	// moves the selected card to the neighbouring column, the selection goes with it
+	pub fn move_selected_card(&mut self, right: bool) {
+		let columns = self.board_columns();
+		let Some((col, _)) = self.selected_card(&columns) else {
+			return;
+		};
+		let target = if right { col + 1 } else { col.wrapping_sub(1) };
+		let (Some(id), Some(to)) = (self.selected_todo, columns.get(target)) else {
+			return;
+		};
+		let from = columns[col].key.clone();
+		let to = to.key.clone();
+		let description = format!("move '{}' to {}", self.selected_title(), to.label());
+		if self.board_grouping == Grouping::Status {
+			self.change_status(description, |list, ids| board::move_card(list, ids, id, &from, &to));
+		} else {
+			let before = self.snapshot(description);
+			let (list, ids) = self.workspace.active_list_and_ids();
+			if board::move_card(list, ids, id, &from, &to) {
+				self.record(before);
+			}
+		}
+		self.board_column = target;
+	}
+
+	pub fn save_popup_input(&mut self) {
+		if let Some(id) = self.selected_todo {
+			self.checkpoint(format!("edit the body of '{}'", self.selected_title()));
+			self.workspace.active_list_mut().update_todo(id, None, Some(self.popup_input.clone()));
+		}
+		self.popup_input.clear();
+		self.reset_cursor();
+		self.input_mode = InputMode::Select;
+		self.show_todo_popup = false;
+	}
+
+	pub fn selected_links(&self) -> &[Link] {
+		self.selected_todo
+			.and_then(|id| self.workspace.active_list().get_todo(id))
+			.map_or(&[], |todo| todo.links())
+	}
+
+	pub fn next_link(&mut self) {
+		let count = self.selected_links().len();
+		if count > 0 {
+			self.selected_link = (self.selected_link + 1) % count;
+		}
+	}
+
+	// urls go to the system opener, files to $EDITOR once the main loop has handed it the terminal
+	pub fn open_selected_link(&mut self) {
+		let Some(link) = self.selected_links().get(self.selected_link).cloned() else {
+			self.alert(String::from("This todo has no links"));
+			return;
+		};
+		match link {
+			Link::Url(url) => match Link::open_url(&url) {
+				Ok(()) => self.notify(format!("Opened {}", url)),
+				Err(e) => self.alert(format!("Could not open {}: {}", url, e)),
+			},
+			file => self.editor_request = Some(file),
+		}
+	}
+
+	pub fn request_delete_selected_todo(&mut self) {
+		if let Some(id) = self.selected_todo {
+			self.ask_confirmation(Confirm::DeleteTodo(id));
+		}
+	}
+
+	// deleted todos go to the trash with their subtasks
+	pub fn remove_selected_todo(&mut self) {
+		if let Some(id) = self.selected_todo {
+			self.checkpoint(format!("delete '{}'", self.selected_title()));
+			self.stop_timers_within(id);
+			let idx = self.selected_index().unwrap_or(0);
+			if let Some(todo) = self.workspace.active_list_mut().remove_todo(id) {
+				let list = self.workspace.active_list().name().to_string();
+				self.workspace.trash_mut().add(&list, todo);
+			}
+			self.select_todo_at(idx);
+			self.sync_tracking();
+		}
+		if self.visible_todos().is_empty() {
+			self.input_mode = InputMode::Visual;
+		}
+	}
+
+	// the clock and the pomodoro can't keep running on a todo that leaves the list, or on one of its subtasks
+	fn stop_timers_within(&mut self, id: TodoId) {
+		let active = self.workspace.active_index();
+		let list = self.workspace.active_list();
+		let within = |todo: TodoId| std::iter::successors(Some(todo), |&todo| list.parent_of(todo)).any(|todo| todo == id);
+		let tracked = self.tracking.as_ref().is_some_and(|tracking| tracking.list == active && within(tracking.todo));
+		let pomodoro = self.pomodoro.as_ref().is_some_and(|pomodoro| pomodoro.list == active && within(pomodoro.todo));
+		if tracked {
+			self.workspace.stop_tracking();
+		}
+		if pomodoro {
+			self.pomodoro = None;
+		}
+	}
+
+	pub fn archive_selected_todo(&mut self) {
+		let Some(id) = self.selected_todo else {
+			return;
+		};
+		let before = self.archive_snapshot(format!("archive '{}'", self.selected_title()));
+		if self.workspace.active_list().get_todo(id).is_some_and(|todo| todo.completed()) {
+			self.stop_timers_within(id);
+		}
+		let idx = self.selected_index().unwrap_or(0);
+		match self.workspace.active_list_mut().archive_todo(id) {
+			Some(todo) => {
+				self.record(before);
+				self.notify(format!("Archived '{}'", todo.title()));
+				self.archive.add(self.workspace.active_list().name(), todo);
+				self.select_todo_at(idx);
+				self.sync_tracking();
+			}
+			None => self.notify(String::from("Only done or cancelled todos can be archived")),
+		}
+		if self.visible_todos().is_empty() {
+			self.input_mode = InputMode::Visual;
+		}
+	}
+
+	// archives what was closed longer ago than the archive_after_days setting, in every list
+	pub fn auto_archive(&mut self) {
+		let Some(days) = self.workspace.settings().archive_after_days else {
+			return;
+		};
+		let cutoff = chrono::Local::now() - chrono::Duration::days(days.into());
+		let before = self.archive_snapshot(format!("archive todos closed more than {} days ago", days));
+		let mut archived = 0;
+		for idx in 0..self.workspace.lists().len() {
+			let Some(list) = self.workspace.list_mut(idx) else {
+				continue;
+			};
+			for todo in list.take_closed_before(cutoff) {
+				self.archive.add(list.name(), todo);
+				archived += 1;
+			}
+		}
+		if archived > 0 {
+			self.record(before);
+			self.sync_todo_list_state();
+			self.notify(format!("Archived {} todo{} closed more than {} days ago", archived, if archived == 1 { "" } else { "s" }, days));
+		}
+	}
+
+	pub fn open_archive(&mut self) {
+		self.input.clear();
+		self.reset_cursor();
+		self.archive_state.select(Some(0));
+		self.input_mode = InputMode::Archive;
+	}
+
+	pub fn close_archive(&mut self) {
+		self.input.clear();
+		self.reset_cursor();
+		self.input_mode = InputMode::Visual;
+	}
+
+	// indices into the archive of the entries matching the search
+	pub fn archive_results(&self) -> Vec<usize> {
+		self.archive.search(&self.input)
+	}
+
+	pub fn move_archive_selection(&mut self, down: bool) {
+		let count = self.archive_results().len();
+		if count == 0 {
+			return;
+		}
+		let idx = self.archive_state.selected().unwrap_or(0).min(count - 1);
+		self.archive_state.select(Some(match down {
+			true => (idx + 1) % count,
+			false => idx.checked_sub(1).unwrap_or(count - 1),
+		}));
+	}
+
+	// puts the selected entry back on top of the list it came from, or the active list when that one is gone
+	pub fn restore_archived(&mut self) {
+		let results = self.archive_results();
+		let Some(&idx) = self.archive_state.selected().and_then(|idx| results.get(idx)) else {
+			return;
+		};
+		let title = self.archive.entries()[idx].todo().title().to_string();
+		let before = self.archive_snapshot(format!("restore '{}'", title));
+		let Some(entry) = self.archive.take(idx) else {
+			return;
+		};
+		self.record(before);
+		let list = entry.list().to_string();
+		self.put_back(&list, entry.into_todo());
+		let count = self.archive_results().len();
+		self.archive_state.select(self.archive_state.selected().map(|idx| idx.min(count.saturating_sub(1))));
+	}
+
+	// puts a todo back on top of the list it was taken from, or the active list when that one is gone
+	fn put_back(&mut self, list: &str, todo: TodoItem) {
+		let original = self.workspace.position_of(list);
+		let target = original.unwrap_or(self.workspace.active_index());
+		let message = format!("Restored '{}' to {}", todo.title(), self.workspace.lists()[target].name());
+		if let Some((list, ids)) = self.workspace.list_and_ids(target) {
+			match original {
+				Some(_) => list.restore_todo(ids, todo),
+				None => list.insert_todo(ids, todo),
+			};
+		}
+		self.notify(message);
+		self.sync_tracking();
+		self.sync_todo_list_state();
+	}
+
+	pub fn open_backups(&mut self) {
+		match backup::list(&self.paths) {
+			Ok(backups) => {
+				self.backups = backups;
+				self.backup_state.select((!self.backups.is_empty()).then_some(0));
+				self.input_mode = InputMode::Backups;
+			}
+			Err(e) => self.alert(format!("Could not list the backups: {}", e)),
+		}
+	}
+
+	pub fn move_backup_selection(&mut self, down: bool) {
+		let count = self.backups.len();
+		if count == 0 {
+			return;
+		}
+		let idx = self.backup_state.selected().unwrap_or(0).min(count - 1);
+		self.backup_state.select(Some(match down {
+			true => (idx + 1) % count,
+			false => idx.checked_sub(1).unwrap_or(count - 1),
+		}));
+	}
+
+	pub fn request_restore_backup(&mut self) {
+		if let Some(idx) = self.backup_state.selected().filter(|&idx| idx < self.backups.len()) {
+			self.ask_confirmation(Confirm::RestoreBackup(idx));
+		}
+	}
+
+	// the lists, settings and trash of the backup replace the ones in memory, the archive stays.
+	// it can be undone like any change and is written on the next save
+	fn restore_backup(&mut self, idx: usize) {
+		let Some(backup) = self.backups.get(idx).cloned() else {
+			return;
+		};
+		let taken_at = backup.taken_at().format("%d-%m-%Y %H:%M:%S");
+		match Workspace::read_from_file(backup.path()) {
+			Ok(workspace) => {
+				self.checkpoint(format!("restore the backup of {}", taken_at));
+				self.replace_workspace(workspace);
+				self.load_list_view();
+				self.sync_tracking();
+				self.input_mode = InputMode::Visual;
+				self.notify(format!("Restored the backup of {}", taken_at));
+			}
+			Err(e) => self.alert(format!("Could not read the backup {}: {}", backup.file_name(), e)),
+		}
+	}
+
+	// ids handed out so far stay taken, the undo history and the archive still hold todos with them
+	fn replace_workspace(&mut self, mut workspace: Workspace) {
+		workspace.continue_ids(&self.workspace);
+		workspace.adopt_archive(&mut self.archive);
+		self.workspace = workspace;
+	}
+
+	pub fn move_recovery_selection(&mut self, down: bool) {
+		let Some(recovery) = &self.recovery else {
+			return;
+		};
+		let count = recovery.choices().len();
+		let idx = self.recovery_state.selected().unwrap_or(0).min(count - 1);
+		self.recovery_state.select(Some(match down {
+			true => (idx + 1) % count,
+			false => idx.checked_sub(1).unwrap_or(count - 1),
+		}));
+	}
+
+	// continues with the salvaged todos, a backup or nothing, and writes it to the data file right away
+	pub fn recover(&mut self) {
+		let Some(recovery) = &mut self.recovery else {
+			return;
+		};
+		let Some(&choice) = recovery.choices().get(self.recovery_state.selected().unwrap_or(0)) else {
+			return;
+		};
+		let (workspace, message) = match choice {
+			Choice::Salvaged => match recovery.take_salvaged() {
+				Some(workspace) => {
+					let message = format!("Continuing with {} salvaged todos", recovery::todo_count(&workspace));
+					(workspace, message)
+				}
+				None => return,
+			},
+			Choice::Backup(idx) => {
+				let backup = &recovery.backups()[idx];
+				let taken_at = backup.taken_at().format("%d-%m-%Y %H:%M:%S");
+				match Workspace::read_from_file(backup.path()) {
+					Ok(workspace) => (workspace, format!("Continuing with the backup of {}", taken_at)),
+					Err(e) => {
+						let message = format!("Could not read the backup {}: {}", backup.file_name(), e);
+						self.alert(message);
+						return;
+					}
+				}
+			}
+			Choice::Fresh => (Workspace::new(), String::from("Starting fresh")),
+		};
+		self.recovery = None;
+		self.replace_workspace(workspace);
+		self.load_list_view();
+		self.sync_tracking();
+		self.input_mode = InputMode::Visual;
+		match self.save() {
+			Ok(()) => self.notify(message),
+			Err(e) => self.alert(format!("Could not save the todos: {}", e)),
+		}
+	}
+
+	pub fn open_trash(&mut self) {
+		self.trash_state.select(Some(0));
+		self.input_mode = InputMode::Trash;
+	}
+
+	pub fn move_trash_selection(&mut self, down: bool) {
+		let count = self.workspace.trash().len();
+		if count == 0 {
+			return;
+		}
+		let idx = self.trash_state.selected().unwrap_or(0).min(count - 1);
+		self.trash_state.select(Some(match down {
+			true => (idx + 1) % count,
+			false => idx.checked_sub(1).unwrap_or(count - 1),
+		}));
+	}
+
+	fn clamp_trash_selection(&mut self) {
+		let count = self.workspace.trash().len();
+		self.trash_state.select(self.trash_state.selected().map(|idx| idx.min(count.saturating_sub(1))));
+	}
+
+	pub fn restore_trashed(&mut self) {
+		let Some(idx) = self.trash_state.selected().filter(|&idx| idx < self.workspace.trash().len()) else {
+			return;
+		};
+		let title = self.workspace.trash().entries()[idx].todo().title().to_string();
+		let before = self.snapshot(format!("restore '{}'", title));
+		let Some(entry) = self.workspace.trash_mut().take(idx) else {
+			return;
+		};
+		self.record(before);
+		let list = entry.list().to_string();
+		self.put_back(&list, entry.into_todo());
+		self.clamp_trash_selection();
+	}
+
+	pub fn request_purge_trashed(&mut self) {
+		if let Some(idx) = self.trash_state.selected().filter(|&idx| idx < self.workspace.trash().len()) {
+			self.ask_confirmation(Confirm::PurgeTrashed(idx));
+		}
+	}
+
+	pub fn request_empty_trash(&mut self) {
+		if !self.workspace.trash().is_empty() {
+			self.ask_confirmation(Confirm::EmptyTrash);
+		}
+	}
+
+	pub fn ask_confirmation(&mut self, confirm: Confirm) {
+		self.confirm_return_mode = self.input_mode;
+		self.input_mode = InputMode::Confirm(confirm);
+	}
+
+	pub fn confirm_message(&self, confirm: Confirm) -> String {
+		match confirm {
+			Confirm::DeleteTodo(id) => {
+				let Some(todo) = self.workspace.active_list().get_todo(id) else {
+					return String::new();
+				};
+				let subtasks = match todo.children().len() {
+					0 => String::new(),
+					1 => String::from(" and its subtask"),
+					count => format!(" and its {} subtasks", count),
+				};
+				format!("Delete '{}'{}? It can be restored from the trash.", todo.title(), subtasks)
+			}
+			Confirm::PurgeTrashed(idx) => format!(
+				"Delete '{}' for good? This can't be undone.",
+				self.workspace.trash().entries().get(idx).map(|entry| entry.todo().title()).unwrap_or_default()
+			),
+			Confirm::EmptyTrash => format!(
+				"Delete all {} todos in the trash for good? This can't be undone.",
+				self.workspace.trash().len()
+			),
+			Confirm::RestoreBackup(idx) => format!(
+				"Replace all lists with the backup of {}? 'u' undoes it.",
+				self.backups.get(idx).map(|backup| backup.taken_at().format("%d-%m-%Y %H:%M:%S").to_string()).unwrap_or_default()
+			),
+		}
+	}
+
+	pub fn answer_confirmation(&mut self, confirm: Confirm, yes: bool) {
+		self.input_mode = self.confirm_return_mode;
+		if !yes {
+			return;
+		}
+		match confirm {
+			Confirm::DeleteTodo(_) => self.remove_selected_todo(),
+			Confirm::PurgeTrashed(idx) => {
+				self.checkpoint(format!(
+					"delete '{}' for good",
+					self.workspace.trash().entries().get(idx).map(|entry| entry.todo().title()).unwrap_or_default()
+				));
+				self.workspace.trash_mut().take(idx);
+				self.clamp_trash_selection();
+			}
+			Confirm::EmptyTrash => {
+				self.checkpoint(String::from("empty the trash"));
+				self.workspace.trash_mut().clear();
+				self.trash_state.select(None);
+			}
+			Confirm::RestoreBackup(idx) => self.restore_backup(idx),
+		}
+	}
+
+	// rebuilds the session stopwatch from the running interval stored in the todos
+	pub fn sync_tracking(&mut self) {
+		self.tracking = self.workspace.tracked_todo().and_then(|(list, todo)| {
+			let running = self.workspace.lists()[list].get_todo(todo)?.running_interval()?.duration();
+			Some(Tracking { list, todo, timer: Timer::stopwatch(running.to_std().unwrap_or_default()) })
+		});
+		self.sync_pomodoro();
+	}
+
+	// the pomodoro follows its todo when it is moved or lists are deleted or undone, and ends when the todo is gone
+	fn sync_pomodoro(&mut self) {
+		let Some(pomodoro) = &self.pomodoro else {
+			return;
+		};
+		let found = self.workspace.lists().iter().position(|list| list.get_todo(pomodoro.todo).is_some());
+		match (found, self.pomodoro.as_mut()) {
+			(Some(list), Some(pomodoro)) => pomodoro.list = list,
+			_ => self.pomodoro = None,
+		}
+	}
+
+	pub fn toggle_tracking(&mut self) {
+		let Some(id) = self.selected_todo else {
+			return;
+		};
+		let active = self.workspace.active_index();
+		if self.tracking.as_ref().is_some_and(|tracking| tracking.list == active && tracking.todo == id) {
+			self.workspace.stop_tracking();
+		} else {
+			self.workspace.start_tracking(id);
+		}
+		self.sync_tracking();
+		self.mark_changed();
+	}
+
+	pub fn stop_tracking(&mut self) {
+		if self.workspace.stop_tracking() {
+			self.mark_changed();
+		}
+		self.sync_tracking();
+	}
+
+	pub fn complete_selected_todo(&mut self, cascade: bool) {
+		if let Some(id) = self.selected_todo {
+			let reopen = self.workspace.active_list().get_todo(id).is_some_and(|todo| todo.status() == Status::Done);
+			let description = format!("{} '{}'", if reopen { "reopen" } else { "complete" }, self.selected_title());
+			self.change_status(description, |list, ids| list.complete_todo(ids, id, cascade));
+		}
+	}
+
+	pub fn set_selected_status(&mut self, status: Status) {
+		if let Some(id) = self.selected_todo {
+			let description = format!("set '{}' to {}", self.selected_title(), status.label());
+			self.change_status(description, |list, ids| list.set_status(ids, id, status, false));
+		}
+	}
+
+	pub fn cycle_selected_status(&mut self) {
+		if let Some(todo) = self.selected_todo.and_then(|id| self.workspace.active_list().get_todo(id)) {
+			self.set_selected_status(todo.status().next());
+		}
+	}
+
+	// closing a todo can unblock the ones waiting on it, those get a notice
+	fn change_status(&mut self, description: String, change: impl FnOnce(&mut TodoList, &mut IdCounter) -> bool) {
+		let before = self.snapshot(description);
+		let blocked_before = self.workspace.active_list().blocked_ids();
+		let (list, ids) = self.workspace.active_list_and_ids();
+		if !change(list, ids) {
+			return;
+		}
+		self.record(before);
+		// closing the tracked todo stops its clock
+		self.sync_tracking();
+		let list = self.workspace.active_list();
+		let blocked_after = list.blocked_ids();
+		let unblocked: Vec<&str> = blocked_before
+			.difference(&blocked_after)
+			.filter_map(|&id| list.get_todo(id))
+			.map(|todo| todo.title())
+			.collect();
+		if !unblocked.is_empty() {
+			self.notify(format!("Unblocked: {}", unblocked.join(", ")));
+		}
+	}
+
+	pub fn notify(&mut self, message: String) {
+		self.notice = Some(Notice { message, shown_at: Instant::now(), alert: false });
+	}
+
+	// rings the terminal bell and shows a highlighted notice
+	pub fn alert(&mut self, message: String) {
+		let mut out = stdout();
+		let _ = out.write_all(b"\x07").and_then(|_| out.flush());
+		self.notice = Some(Notice { message, shown_at: Instant::now(), alert: true });
+	}
+
+	// notices stay on screen for a few seconds, alerts a bit longer
+	pub fn current_notice(&self) -> Option<&Notice> {
+		self.notice.as_ref().filter(|notice| {
+			let shown_for = if notice.alert { Duration::from_secs(10) } else { Duration::from_secs(4) };
+			notice.shown_at.elapsed() < shown_for
+		})
+	}
+
+	// starts a pomodoro on the selected todo, or stops the one running on it
+	pub fn toggle_pomodoro(&mut self) {
+		let Some(id) = self.selected_todo else {
+			return;
+		};
+		let active = self.workspace.active_index();
+		if self.pomodoro.as_ref().is_some_and(|pomodoro| pomodoro.list == active && pomodoro.todo == id) {
+			self.pomodoro = None;
+			self.notify(String::from("Pomodoro stopped"));
+		} else if self.workspace.active_list().get_todo(id).is_some() {
+			self.pomodoro = Some(Pomodoro::new(active, id, self.workspace.settings().pomodoro));
+		}
+	}
+
+	fn update_pomodoro(&mut self) {
+		let Some(pomodoro) = self.pomodoro.as_mut() else {
+			return;
+		};
+		let Some(ended) = pomodoro.tick() else {
+			return;
+		};
+		let (list, todo, next) = (pomodoro.list, pomodoro.todo, pomodoro.phase());
+		if ended == Phase::Focus {
+			if let Some(list) = self.workspace.list_mut(list) {
+				list.record_pomodoro(todo);
+			}
+			self.mark_changed();
+		}
+		self.alert(format!("{} is over, {} starts now", ended.label(), next.label().to_lowercase()));
+	}
+
+	// collapses the selected todo, or moves up to its parent when there is nothing to collapse
+	pub fn collapse_selected_todo(&mut self) {
+		if let Some(id) = self.selected_todo {
+			// moving up to the parent only changes the selection, which is not saved on its own
+			if self.workspace.active_list_mut().set_collapsed(id, true) {
+				self.mark_changed();
+			} else if let Some(parent) = self.workspace.active_list().parent_of(id) {
+				self.selected_todo = Some(parent);
+			}
+			self.sync_todo_list_state();
+		}
+	}
+
+	pub fn expand_selected_todo(&mut self) {
+		if let Some(id) = self.selected_todo {
+			if self.workspace.active_list_mut().set_collapsed(id, false) {
+				self.mark_changed();
+			}
+		}
+	}
+
+	pub fn change_selected_priority(&mut self, raise: bool) {
+		let Some(id) = self.selected_todo else {
+			return;
+		};
+		if let Some(priority) = self.workspace.active_list().get_todo(id).map(|todo| todo.priority()) {
+			self.checkpoint(format!("change the priority of '{}'", self.selected_title()));
+			let priority = if raise { priority.raised() } else { priority.lowered() };
+			self.workspace.active_list_mut().set_priority(id, priority);
+			self.workspace.active_list_mut().sort(self.sort_order);
+		}
+	}
+
+	// remembers the selection and sort order of the shown list so they come back with it
+	pub fn store_list_view(&mut self) {
+		let (sort_order, selected) = (self.sort_order, self.selected_todo);
+		self.workspace.active_list_mut().set_view(sort_order, selected);
+	}
+
+	fn load_list_view(&mut self) {
+		self.sort_order = self.workspace.active_list().sort_order();
+		self.selected_todo = self.workspace.active_list().selected();
+		self.sync_todo_list_state();
+		self.sync_pomodoro();
+	}
+
+	pub fn switch_list(&mut self, idx: usize) {
+		self.store_list_view();
+		self.workspace.set_active(idx);
+		self.load_list_view();
+	}
+
+	pub fn next_list(&mut self) {
+		let count = self.workspace.lists().len();
+		self.switch_list((self.workspace.active_index() + 1) % count);
+	}
+
+	pub fn previous_list(&mut self) {
+		let count = self.workspace.lists().len();
+		self.switch_list((self.workspace.active_index() + count - 1) % count);
+	}
+
+	pub fn move_todo_to_list(&mut self, id: TodoId, target: usize) {
+		let idx = self.selected_index().unwrap_or(0);
+		let Some(todo) = self.workspace.active_list_mut().remove_todo(id) else {
+			return;
+		};
+		if let Some((list, ids)) = self.workspace.list_and_ids(target) {
+			list.insert_todo(ids, todo);
+		}
+		self.select_todo_at(idx);
+		self.sync_tracking();
+		if self.selected_todo.is_none() {
+			self.prompt_return_mode = InputMode::Visual;
+		}
+	}
+
+	pub fn cycle_sort_order(&mut self) {
+		let fields = self.workspace.active_list().fields();
+		let next = self.sort_order.next(fields);
+		self.checkpoint(format!("sort by {}", next.label(fields)));
+		self.sort_order = next;
+		self.workspace.active_list_mut().sort(self.sort_order);
+	}
+
+	pub fn cycle_filter(&mut self) {
+		self.filter = self.filter.next();
+		self.sync_todo_list_state();
+		if self.selected_todo.is_none() && self.input_mode == InputMode::Select {
+			self.input_mode = InputMode::Visual;
+		}
+	}
+
+	pub fn open_prompt(&mut self, prompt: Prompt) {
+		if prompt.needs_todo() && self.selected_todo.is_none() {
+			return;
+		}
+		let selected = self.selected_todo.and_then(|id| self.workspace.active_list().get_todo(id));
+		self.input = match prompt {
+			Prompt::DueDate => selected
+				.and_then(|todo| todo.due_date())
+				.map(|due| due.format("%d-%m-%Y %H:%M").to_string())
+				.unwrap_or_default(),
+			Prompt::Tags => selected
+				.map(|todo| todo.tags().cloned().collect::<Vec<_>>().join(" "))
+				.unwrap_or_default(),
+			Prompt::Recurrence => selected
+				.and_then(|todo| todo.recurrence())
+				.map(|recurrence| recurrence.describe())
+				.unwrap_or_default(),
+			Prompt::BlockedBy => selected
+				.map(|todo| todo.blocked_by().map(|id| format!("#{}", id)).collect::<Vec<_>>().join(" "))
+				.unwrap_or_default(),
+			Prompt::RenameList => self.workspace.active_list().name().to_string(),
+			Prompt::PomodoroSettings => self.workspace.settings().pomodoro.describe(),
+			Prompt::ArchiveAfter => self.workspace.settings().archive_after_days.map(|days| days.to_string()).unwrap_or_default(),
+			Prompt::TrashRetention => self.workspace.settings().trash_retention_days.to_string(),
+			Prompt::BackupCount => self.workspace.settings().backup_count.to_string(),
+			Prompt::Fields => FieldDef::describe_all(self.workspace.active_list().fields()),
+			Prompt::EditField | Prompt::Note => String::new(),
+			Prompt::Estimate => selected
+				.and_then(|todo| todo.estimate())
+				.map(Estimate::describe)
+				.unwrap_or_default(),
+			Prompt::FieldFilter => match &self.filter {
+				Filter::Field(filter) => filter.describe(),
+				_ => String::new(),
+			},
+			Prompt::NewList | Prompt::DeleteList | Prompt::MoveToList => String::new(),
+			Prompt::TagFilter => match &self.filter {
+				Filter::Tags(tags) => tags.join(" "),
+				_ => String::new(),
+			},
+		};
+		self.cursor_index = self.input.chars().count();
+		self.prompt_return_mode = self.input_mode;
+		self.input_mode = InputMode::Prompt(prompt);
+	}
+
+	pub fn close_prompt(&mut self) {
+		self.input.clear();
+		self.reset_cursor();
+		self.input_mode = self.prompt_return_mode;
+		if self.input_mode == InputMode::Select && self.selected_index().is_none() {
+			self.input_mode = InputMode::Visual;
+		}
+	}
+
+	// returns false when the input is not valid for the prompt, so it stays open
+	pub fn submit_prompt(&mut self, prompt: Prompt) -> bool {
+		let input = self.input.trim().to_string();
+		// kept only once the change went through
+		let before = match prompt.change_description() {
+			Some(change) => {
+				let description = match prompt {
+					Prompt::NewList => format!("{} '{}'", change, input),
+					_ if prompt.needs_todo() => format!("{} '{}'", change, self.selected_title()),
+					_ => format!("{} '{}'", change, self.workspace.active_list().name()),
+				};
+				Some(self.snapshot(description))
+			}
+			None => None,
+		};
+		match (prompt, self.selected_todo) {
+			(Prompt::DueDate, Some(id)) => {
+				if input.is_empty() {
+					self.workspace.active_list_mut().set_due_date(id, None);
+				} else if let Some(due) = parse_due_date(&input) {
+					self.workspace.active_list_mut().set_due_date(id, Some(due));
+				} else {
+					return false;
+				}
+			}
+			(Prompt::Note, Some(id)) => {
+				if input.is_empty() {
+					return false;
+				}
+				self.workspace.active_list_mut().add_note(id, input);
+			}
+			(Prompt::Estimate, Some(id)) => {
+				if input.is_empty() {
+					self.workspace.active_list_mut().set_estimate(id, None);
+				} else if let Some(estimate) = Estimate::parse(&input) {
+					self.workspace.active_list_mut().set_estimate(id, Some(estimate));
+				} else {
+					return false;
+				}
+			}
+			(Prompt::Tags, Some(id)) => {
+				self.workspace.active_list_mut().set_tags(id, parse_tags(&input));
+			}
+			(Prompt::TagFilter, _) => {
+				let tags: Vec<String> = parse_tags(&input).into_iter().collect();
+				self.filter = if tags.is_empty() { Filter::All } else { Filter::Tags(tags) };
+			}
+			(Prompt::Recurrence, Some(id)) => {
+				if input.is_empty() {
+					self.workspace.active_list_mut().set_recurrence(id, None);
+				} else if let Some(recurrence) = Recurrence::parse(&input) {
+					self.workspace.active_list_mut().set_recurrence(id, Some(recurrence));
+				} else {
+					return false;
+				}
+			}
+			(Prompt::NewList, _) => {
+				let Some(idx) = self.workspace.add_list(&input) else {
+					return false;
+				};
+				self.switch_list(idx);
+				self.prompt_return_mode = InputMode::Visual;
+			}
+			(Prompt::RenameList, _) => {
+				if !self.workspace.rename_active(&input) {
+					return false;
+				}
+			}
+			(Prompt::DeleteList, _) => {
+				if !input.eq_ignore_ascii_case(self.workspace.active_list().name()) || self.workspace.lists().len() <= 1 {
+					return false;
+				}
+				let active = self.workspace.active_index();
+				if self.tracking.as_ref().is_some_and(|tracking| tracking.list == active) {
+					self.workspace.stop_tracking();
+				}
+				if self.pomodoro.as_ref().is_some_and(|pomodoro| pomodoro.list == active) {
+					self.pomodoro = None;
+				}
+				let Some(removed) = self.workspace.remove_active() else {
+					return false;
+				};
+				let name = removed.name().to_string();
+				for todo in removed.into_todos() {
+					self.workspace.trash_mut().add(&name, todo);
+				}
+				self.load_list_view();
+				self.sync_tracking();
+				self.prompt_return_mode = InputMode::Visual;
+			}
+			(Prompt::MoveToList, Some(id)) => {
+				match self.workspace.position_of(&input) {
+					Some(target) if target != self.workspace.active_index() => self.move_todo_to_list(id, target),
+					_ => return false,
+				}
+			}
+			(Prompt::PomodoroSettings, _) => {
+				let Some(settings) = PomodoroSettings::parse(&input) else {
+					return false;
+				};
+				self.workspace.settings_mut().pomodoro = settings;
+			}
+			(Prompt::ArchiveAfter, _) => {
+				if input.is_empty() {
+					self.workspace.settings_mut().archive_after_days = None;
+				} else if let Ok(days) = input.parse() {
+					self.workspace.settings_mut().archive_after_days = Some(days);
+					self.auto_archive();
+				} else {
+					return false;
+				}
+			}
+			(Prompt::Fields, _) => {
+				let Some(fields) = FieldDef::parse_all(&input) else {
+					return false;
+				};
+				self.workspace.active_list_mut().set_fields(fields);
+				if matches!(self.sort_order, SortOrder::Field(idx) if idx >= self.workspace.active_list().fields().len()) {
+					self.sort_order = SortOrder::CreationDate;
+				}
+			}
+			(Prompt::EditField, Some(id)) => {
+				let (name, value) = input.split_once('=').unwrap_or((&input, ""));
+				let (name, value) = (name.trim().to_lowercase(), value.trim());
+				let Some(kind) = self.workspace.active_list().field_def(&name).map(|field| field.kind) else {
+					return false;
+				};
+				let value = match value {
+					"" => None,
+					value => match FieldValue::parse(kind, value) {
+						Some(value) => Some(value),
+						None => return false,
+					},
+				};
+				self.workspace.active_list_mut().set_field(id, &name, value);
+			}
+			(Prompt::FieldFilter, _) => {
+				if input.is_empty() {
+					self.filter = Filter::All;
+				} else {
+					let Some(filter) = FieldFilter::parse(&input) else {
+						return false;
+					};
+					self.filter = Filter::Field(filter);
+				}
+			}
+			(Prompt::TrashRetention, _) => {
+				let Ok(days) = input.parse() else {
+					return false;
+				};
+				self.workspace.settings_mut().trash_retention_days = days;
+			}
+			(Prompt::BackupCount, _) => {
+				let Some(count) = input.parse().ok().filter(|&count| count > 0) else {
+					return false;
+				};
+				self.workspace.settings_mut().backup_count = count;
+			}
+			(Prompt::BlockedBy, Some(id)) => {
+				let Some(blockers) = parse_todo_ids(&input) else {
+					return false;
+				};
+				if let Err(e) = self.workspace.active_list_mut().set_blocked_by(id, blockers) {
+					self.notify(format!("Can't set blockers: {}", e));
+					return false;
+				}
+			}
+			(Prompt::DueDate | Prompt::Tags | Prompt::Recurrence | Prompt::MoveToList | Prompt::BlockedBy | Prompt::EditField | Prompt::Estimate | Prompt::Note, None) => {}
+		}
+		match before {
+			Some(before) => self.record(before),
+			// settings are saved with the lists but are not undo steps, filters are not saved
+			None if !matches!(prompt, Prompt::TagFilter | Prompt::FieldFilter) => self.mark_changed(),
+			None => {}
+		}
+		self.workspace.active_list_mut().sort(self.sort_order);
+		self.sync_todo_list_state();
+		true
 	}
 }
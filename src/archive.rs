@@ -0,0 +1,94 @@
+use std::path::Path;
+use chrono::{DateTime, Local, Utc};
+use serde::{Serialize, Deserialize};
+use serde_json;
+
+use crate::schema::{self, Kind};
+use crate::todo::TodoItem;
+use crate::storage::write_atomically;
+
+// todos taken out of their lists, kept in a file of their own so the data file stays small
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Archive {
+	entries: Vec<ArchivedTodo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedTodo {
+	// name of the list it came from, restoring puts it back there when the list still exists
+	list: String,
+	#[serde(with = "chrono::serde::ts_seconds")]
+	archived_at: DateTime<Utc>,
+	todo: TodoItem,
+}
+
+impl ArchivedTodo {
+	pub fn list(&self) -> &str {
+		&self.list
+	}
+	pub fn archived_at(&self) -> DateTime<Local> {
+		self.archived_at.with_timezone(&chrono::Local)
+	}
+	pub fn todo(&self) -> &TodoItem {
+		&self.todo
+	}
+	pub fn into_todo(self) -> TodoItem {
+		self.todo
+	}
+	pub fn todo_mut(&mut self) -> &mut TodoItem {
+		&mut self.todo
+	}
+}
+
+impl Archive {
+	pub fn new() -> Self {
+		Archive { entries: Vec::new() }
+	}
+
+	pub fn entries(&self) -> &[ArchivedTodo] {
+		&self.entries
+	}
+
+	pub fn entries_mut(&mut self) -> &mut [ArchivedTodo] {
+		&mut self.entries
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	// the most recently archived todo comes first
+	pub fn add(&mut self, list: &str, todo: TodoItem) {
+		self.entries.insert(0, ArchivedTodo { list: list.to_string(), archived_at: Utc::now(), todo });
+	}
+
+	// indices of the entries matching the query in their todo or list name, an empty query matches everything
+	pub fn search(&self, query: &str) -> Vec<usize> {
+		let query = query.trim().to_lowercase();
+		self.entries.iter()
+			.enumerate()
+			.filter(|(_, entry)| entry.todo.contains_text(&query) || entry.list.to_lowercase().contains(&query))
+			.map(|(idx, _)| idx)
+			.collect()
+	}
+
+	pub fn take(&mut self, idx: usize) -> Option<ArchivedTodo> {
+		(idx < self.entries.len()).then(|| self.entries.remove(idx))
+	}
+
+	// versioned like the data file, its todos go through the same migrations
+	pub fn save_to_file(&self, filename: &Path) -> std::io::Result<()> {
+		let json = schema::to_versioned_json(self)?;
+		write_atomically(filename, json.as_bytes())
+	}
+
+	pub fn load_from_file(filename: &Path) -> std::io::Result<Self> {
+		let document = schema::read_migrated(filename, Kind::Archive)?;
+		let archive = serde_json::from_value(document)?;
+		Ok(archive)
+	}
+}
@@ -0,0 +1,169 @@
+use std::{fs, io, path::{Path, PathBuf}};
+use chrono::{Local, NaiveDateTime};
+
+use crate::paths::DataPaths;
+use crate::storage::write_atomically;
+use crate::workspace::Workspace;
+
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+// a copy of the data file, named after it with the time it was taken: backups/todos-20240131-094500.json
+#[derive(Debug, Clone)]
+pub struct Backup {
+	path: PathBuf,
+	taken_at: NaiveDateTime,
+	size: u64,
+}
+
+impl Backup {
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+	pub fn taken_at(&self) -> NaiveDateTime {
+		self.taken_at
+	}
+	pub fn size(&self) -> u64 {
+		self.size
+	}
+	pub fn file_name(&self) -> String {
+		self.path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+	}
+}
+
+// backups of the data file, the newest first. a missing backup directory means there are none
+pub fn list(paths: &DataPaths) -> io::Result<Vec<Backup>> {
+	let entries = match fs::read_dir(paths.backup_dir()) {
+		Ok(entries) => entries,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+		Err(e) => return Err(e),
+	};
+	let prefix = format!("{}-", paths.data_stem());
+	let mut backups = Vec::new();
+	for entry in entries {
+		let entry = entry?;
+		let name = entry.file_name().to_string_lossy().into_owned();
+		let Some(timestamp) = name.strip_prefix(&prefix).and_then(|rest| rest.strip_suffix(".json")) else {
+			continue;
+		};
+		if let Ok(taken_at) = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT) {
+			backups.push(Backup { path: entry.path(), taken_at, size: entry.metadata()?.len() });
+		}
+	}
+	backups.sort_by_key(|backup| std::cmp::Reverse(backup.taken_at));
+	Ok(backups)
+}
+
+// copies the data file into the backups and drops the oldest ones past keep. nothing to copy is not an error
+pub fn create(paths: &DataPaths, keep: usize) -> io::Result<Option<PathBuf>> {
+	let contents = match fs::read(paths.data_file()) {
+		Ok(contents) => contents,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+		Err(e) => return Err(e),
+	};
+	fs::create_dir_all(paths.backup_dir())?;
+	let name = format!("{}-{}.json", paths.data_stem(), Local::now().format(TIMESTAMP_FORMAT));
+	let path = paths.backup_dir().join(name);
+	write_atomically(&path, &contents)?;
+	for old in list(paths)?.iter().skip(keep.max(1)) {
+		fs::remove_file(old.path())?;
+	}
+	Ok(Some(path))
+}
+
+// puts the backup in place of the data file, after backing up the data file as it is so this can be undone.
+// a backup that does not load is not restored, it is copied as it is and migrated when the data file loads
+pub fn restore(paths: &DataPaths, backup: &Backup, keep: usize) -> io::Result<()> {
+	Workspace::read_from_file(backup.path())?;
+	// read first, it may be the oldest backup and get dropped
+	let contents = fs::read(backup.path())?;
+	create(paths, keep)?;
+	write_atomically(paths.data_file(), &contents)
+}
+
+// "1.5 KB"
+pub fn format_size(size: u64) -> String {
+	match size {
+		0..=1023 => format!("{} B", size),
+		1024..=1048575 => format!("{:.1} KB", size as f64 / 1024.0),
+		_ => format!("{:.1} MB", size as f64 / 1048576.0),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::storage::tests::scratch_dir;
+
+	fn names(backups: &[Backup]) -> Vec<String> {
+		backups.iter().map(Backup::file_name).collect()
+	}
+
+	#[test]
+	fn nothing_to_back_up_is_not_an_error() {
+		let dir = scratch_dir("backup_nothing");
+		let paths = DataPaths::new(dir.join("todos.json"));
+		assert_eq!(create(&paths, 3).unwrap(), None);
+		assert!(list(&paths).unwrap().is_empty());
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn oldest_backups_past_keep_are_dropped() {
+		let dir = scratch_dir("backup_prune");
+		let paths = DataPaths::new(dir.join("todos.json"));
+		fs::write(paths.data_file(), "current").unwrap();
+		fs::create_dir_all(paths.backup_dir()).unwrap();
+		for name in ["todos-20240101-080000.json", "todos-20240102-080000.json", "todos-20240103-080000.json"] {
+			fs::write(paths.backup_dir().join(name), "old").unwrap();
+		}
+		// backups of other data files and files that aren't backups are left alone
+		for name in ["work-20230101-080000.json", "todos-notes.json"] {
+			fs::write(paths.backup_dir().join(name), "other").unwrap();
+		}
+		let created = create(&paths, 2).unwrap().unwrap();
+		assert_eq!(fs::read_to_string(&created).unwrap(), "current");
+		let backups = list(&paths).unwrap();
+		assert_eq!(backups[0].path(), created);
+		assert_eq!(names(&backups[1..]), ["todos-20240103-080000.json"]);
+		assert!(paths.backup_dir().join("work-20230101-080000.json").exists());
+		assert!(paths.backup_dir().join("todos-notes.json").exists());
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	// a version 1 file, which loading migrates
+	const OLD_DATA: &str = r#"{ "version": 1, "lists": [{ "name": "Old", "todos": [] }] }"#;
+
+	#[test]
+	fn restoring_backs_up_the_data_file_first() {
+		let dir = scratch_dir("backup_restore");
+		let paths = DataPaths::new(dir.join("todos.json"));
+		fs::create_dir_all(paths.backup_dir()).unwrap();
+		fs::write(paths.backup_dir().join("todos-20240101-080000.json"), OLD_DATA).unwrap();
+		Workspace::new().save_to_file(paths.data_file()).unwrap();
+		let current = fs::read_to_string(paths.data_file()).unwrap();
+		let old = list(&paths).unwrap().remove(0);
+		// the backup being restored is the one that gets dropped
+		restore(&paths, &old, 1).unwrap();
+		assert_eq!(fs::read_to_string(paths.data_file()).unwrap(), OLD_DATA);
+		let backups = list(&paths).unwrap();
+		assert_eq!(backups.len(), 1);
+		assert_eq!(fs::read_to_string(backups[0].path()).unwrap(), current);
+		// reading the old backup left no copy of it in the backups
+		assert_eq!(fs::read_dir(paths.backup_dir()).unwrap().count(), 1);
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn backup_that_does_not_load_is_not_restored() {
+		let dir = scratch_dir("backup_broken");
+		let paths = DataPaths::new(dir.join("todos.json"));
+		fs::create_dir_all(paths.backup_dir()).unwrap();
+		fs::write(paths.backup_dir().join("todos-20240101-080000.json"), "{ \"lists\": [").unwrap();
+		fs::write(paths.data_file(), OLD_DATA).unwrap();
+		let broken = list(&paths).unwrap().remove(0);
+		assert!(restore(&paths, &broken, 5).is_err());
+		assert_eq!(fs::read_to_string(paths.data_file()).unwrap(), OLD_DATA);
+		assert_eq!(list(&paths).unwrap().len(), 1);
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
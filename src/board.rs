@@ -0,0 +1,103 @@
+use std::collections::BTreeSet;
+
+use crate::status::Status;
+use crate::todo::{Filter, IdCounter, TodoId, TodoList};
+
+#[derive(PartialEq, Debug, Default, Clone, Copy)]
+pub enum Grouping {
+	#[default]
+	Status,
+	Tag,
+}
+
+impl Grouping {
+	pub fn next(self) -> Self {
+		match self {
+			Grouping::Status => Grouping::Tag,
+			Grouping::Tag => Grouping::Status,
+		}
+	}
+
+	pub fn label(self) -> &'static str {
+		match self {
+			Grouping::Status => "status",
+			Grouping::Tag => "tag",
+		}
+	}
+}
+
+// what a todo has in common with the other cards of its column
+#[derive(PartialEq, Debug, Clone)]
+pub enum ColumnKey {
+	Status(Status),
+	Tag(String),
+	Untagged,
+}
+
+impl ColumnKey {
+	pub fn label(&self) -> &str {
+		match self {
+			ColumnKey::Status(status) => status.label(),
+			ColumnKey::Tag(tag) => tag,
+			ColumnKey::Untagged => "untagged",
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Column {
+	pub key: ColumnKey,
+	pub todos: Vec<TodoId>,
+}
+
+// the visible todos of the list in board columns, in the order the list shows them.
+// columns don't depend on the filter so they stay in place while filtering, a todo with
+// several tags has a card in each of their columns
+pub fn columns(list: &TodoList, filter: &Filter, grouping: Grouping) -> Vec<Column> {
+	let keys: Vec<ColumnKey> = match grouping {
+		Grouping::Status => Status::ALL.into_iter().map(ColumnKey::Status).collect(),
+		Grouping::Tag => {
+			let tags: BTreeSet<&String> = list.walk().into_iter().flat_map(|todo| todo.tags()).collect();
+			std::iter::once(ColumnKey::Untagged)
+				.chain(tags.into_iter().map(|tag| ColumnKey::Tag(tag.clone())))
+				.collect()
+		}
+	};
+	let visible = list.visible_ids(filter);
+	keys.into_iter()
+		.map(|key| {
+			let todos = visible.iter()
+				.copied()
+				.filter(|&id| list.get_todo(id).is_some_and(|todo| match &key {
+					ColumnKey::Status(status) => todo.status() == *status,
+					ColumnKey::Tag(tag) => todo.tags().any(|todo_tag| todo_tag == tag),
+					ColumnKey::Untagged => todo.tags().next().is_none(),
+				}))
+				.collect();
+			Column { key, todos }
+		})
+		.collect()
+}
+
+// gives the todo what the target column has in common, for tags it trades the tag of the column it leaves
+pub fn move_card(list: &mut TodoList, ids: &mut IdCounter, id: TodoId, from: &ColumnKey, to: &ColumnKey) -> bool {
+	match to {
+		ColumnKey::Status(status) => list.set_status(ids, id, *status, false),
+		ColumnKey::Tag(_) | ColumnKey::Untagged => {
+			let Some(todo) = list.get_todo(id) else {
+				return false;
+			};
+			let mut tags: BTreeSet<String> = todo.tags().cloned().collect();
+			if let ColumnKey::Tag(tag) = from {
+				tags.remove(tag);
+			}
+			match to {
+				ColumnKey::Tag(tag) => {
+					tags.insert(tag.clone());
+				}
+				_ => tags.clear(),
+			}
+			list.set_tags(id, tags)
+		}
+	}
+}
@@ -0,0 +1,31 @@
+use chrono::{DateTime, Local, Utc};
+use serde::{Serialize, Deserialize};
+
+// one edit of a field of a todo, values are kept as they were shown. the log of a todo is only ever appended to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldChange {
+	field: String,
+	old: String,
+	new: String,
+	#[serde(with = "chrono::serde::ts_seconds")]
+	at: DateTime<Utc>,
+}
+
+impl FieldChange {
+	pub fn now(field: &str, old: String, new: String) -> Self {
+		FieldChange { field: field.to_string(), old, new, at: Utc::now() }
+	}
+
+	pub fn field(&self) -> &str {
+		&self.field
+	}
+	pub fn old(&self) -> &str {
+		&self.old
+	}
+	pub fn new_value(&self) -> &str {
+		&self.new
+	}
+	pub fn at(&self) -> DateTime<Local> {
+		self.at.with_timezone(&chrono::Local)
+	}
+}
@@ -0,0 +1,188 @@
+use chrono::Duration;
+use serde::{Serialize, Deserialize};
+
+use crate::status::Status;
+use crate::todo::{Filter, TodoItem, TodoList};
+
+// how much work a todo is expected to be, as time or as story points
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum Estimate {
+	Minutes(u32),
+	Points(u32),
+}
+
+impl Estimate {
+	// "45m", "2h", "1h30m", "1.5h" or a bare number of minutes, "3p", "3pt" or "3 points" for story points
+	pub fn parse(input: &str) -> Option<Self> {
+		let input = input.trim().to_lowercase();
+		for suffix in ["points", "point", "pts", "pt", "sp", "p"] {
+			if let Some(points) = input.strip_suffix(suffix) {
+				return points.trim().parse().ok().map(Estimate::Points);
+			}
+		}
+		if let Ok(minutes) = input.parse() {
+			return Some(Estimate::Minutes(minutes));
+		}
+		let mut minutes = 0.0;
+		let mut number = String::new();
+		let mut has_unit = false;
+		for c in input.chars() {
+			match c {
+				'0'..='9' | '.' => number.push(c),
+				'h' | 'm' => {
+					let value: f64 = number.parse().ok()?;
+					minutes += if c == 'h' { value * 60.0 } else { value };
+					number.clear();
+					has_unit = true;
+				}
+				' ' => {}
+				_ => return None,
+			}
+		}
+		if !has_unit || !number.is_empty() {
+			return None;
+		}
+		Some(Estimate::Minutes(minutes.round() as u32))
+	}
+
+	// written so that parse gives back the same estimate
+	pub fn describe(self) -> String {
+		match self {
+			Estimate::Minutes(minutes) => format_minutes(i64::from(minutes)),
+			Estimate::Points(points) => format!("{}pt", points),
+		}
+	}
+}
+
+// "45m", "2h" or "1h30m"
+pub fn format_minutes(minutes: i64) -> String {
+	match (minutes / 60, minutes % 60) {
+		(0, minutes) => format!("{}m", minutes),
+		(hours, 0) => format!("{}h", hours),
+		(hours, minutes) => format!("{}h{:02}m", hours, minutes),
+	}
+}
+
+// estimated work of several todos, time and points are kept apart since they do not convert
+#[derive(PartialEq, Debug, Default, Clone, Copy)]
+pub struct Workload {
+	pub minutes: u32,
+	pub points: u32,
+	pub todos: usize,
+	// todos counted without an estimate
+	pub unestimated: usize,
+}
+
+impl Workload {
+	fn add(&mut self, todo: &TodoItem) {
+		self.todos += 1;
+		match todo.estimate() {
+			// estimates are not capped, a few huge ones must not overflow the sum
+			Some(Estimate::Minutes(minutes)) => self.minutes = self.minutes.saturating_add(minutes),
+			Some(Estimate::Points(points)) => self.points = self.points.saturating_add(points),
+			None => self.unestimated += 1,
+		}
+	}
+
+	// open todos of the list the filter shows, subtasks included
+	pub fn remaining(list: &TodoList, filter: &Filter) -> Self {
+		let mut workload = Workload::default();
+		for todo in list.walk().into_iter().filter(|todo| !todo.completed() && filter.matches(todo, list)) {
+			workload.add(todo);
+		}
+		workload
+	}
+
+	pub fn describe(&self) -> String {
+		let mut parts = Vec::new();
+		if self.minutes > 0 {
+			parts.push(format_minutes(i64::from(self.minutes)));
+		}
+		if self.points > 0 {
+			parts.push(format!("{}pt", self.points));
+		}
+		let estimated = match parts.is_empty() {
+			true => String::from("nothing estimated"),
+			false => parts.join(" + "),
+		};
+		match self.unestimated {
+			0 => estimated,
+			count => format!("{}, {} without estimate", estimated, count),
+		}
+	}
+}
+
+// a done todo with an estimate next to the time tracked on it
+pub struct ReportRow<'a> {
+	pub todo: &'a TodoItem,
+	pub estimate: Estimate,
+	pub actual: Duration,
+}
+
+impl ReportRow<'_> {
+	// minutes a time estimate was off by, positive when it took longer. None for points
+	pub fn overrun(&self) -> Option<i64> {
+		match self.estimate {
+			Estimate::Minutes(minutes) => Some(self.actual.num_minutes() - i64::from(minutes)),
+			Estimate::Points(_) => None,
+		}
+	}
+}
+
+// done todos of the list with an estimate, the most recently closed first
+pub fn done_with_estimate(list: &TodoList) -> Vec<ReportRow<'_>> {
+	let mut rows: Vec<ReportRow> = list.walk()
+		.into_iter()
+		.filter(|todo| todo.status() == Status::Done)
+		.filter_map(|todo| todo.estimate().map(|estimate| ReportRow { todo, estimate, actual: todo.tracked_time() }))
+		.collect();
+	rows.sort_by_key(|row| std::cmp::Reverse(row.todo.closed_at()));
+	rows
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_time_units() {
+		for (input, minutes) in [("45m", 45), ("2h", 120), ("1h30m", 90), ("1h 30m", 90), ("1.5h", 90), ("90", 90), (" 2H ", 120), ("0.25h", 15)] {
+			assert_eq!(Estimate::parse(input), Some(Estimate::Minutes(minutes)), "{}", input);
+		}
+	}
+
+	#[test]
+	fn parses_points() {
+		for input in ["3p", "3pt", "3pts", "3sp", "3 point", "3 points"] {
+			assert_eq!(Estimate::parse(input), Some(Estimate::Points(3)), "{}", input);
+		}
+	}
+
+	#[test]
+	fn rejects_bad_input() {
+		for input in ["", "h", "2x", "1h30", "m30", "-2h", "1..5h", "three points", "2h p"] {
+			assert_eq!(Estimate::parse(input), None, "{}", input);
+		}
+	}
+
+	#[test]
+	fn describe_parses_back() {
+		for estimate in [Estimate::Minutes(45), Estimate::Minutes(120), Estimate::Minutes(95), Estimate::Points(5)] {
+			assert_eq!(Estimate::parse(&estimate.describe()), Some(estimate));
+		}
+	}
+
+	#[test]
+	fn huge_estimates_do_not_overflow_the_workload() {
+		let mut list = TodoList::new();
+		let mut ids = crate::todo::IdCounter::default();
+		for title in ["a", "b", "c"] {
+			let id = list.add_todo(&mut ids, title.to_string(), None);
+			list.set_estimate(id, Estimate::parse("99999999h"));
+		}
+		let workload = Workload::remaining(&list, &Filter::All);
+		assert_eq!(workload.minutes, u32::MAX);
+		assert_eq!(workload.todos, 3);
+		assert!(!workload.describe().is_empty());
+	}
+}
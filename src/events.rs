@@ -1,58 +1,119 @@
 use std::io;
-use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
-use crate::app::{App, InputMode};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crate::app::{App, InputMode, Prompt, View};
+use crate::status::Status;
 
-pub fn handle_events(app: &mut App) -> io::Result<bool> {
-    if event::poll(std::time::Duration::from_millis(50))? {
-        if let Event::Key(key) = event::read()? {
+// waits a little for input, the app is not needed for that and stays free for the signal handler meanwhile
+pub fn next_event() -> io::Result<Option<Event>> {
+	if event::poll(std::time::Duration::from_millis(50))? {
+		return event::read().map(Some);
+	}
+	Ok(None)
+}
+
+// true when the user quits, main saves once it gave the terminal back
+pub fn handle_events(app: &mut App, event: Option<Event>) -> io::Result<bool> {
+    if let Some(Event::Key(key)) = event {
+			// raw mode turns Ctrl-C into a key, it quits from anywhere
+			if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+				return Ok(true);
+			}
 			if key.kind == KeyEventKind::Press {
 				match app.input_mode {
 					InputMode::Visual => match key.code {
-						KeyCode::Char('q') => {
-							match app.todo_list.save_to_file(".todo_temp.json") {
-								Ok(()) => {
-									println!("Successfuly saved todos in temp file");
-								}
-								Err(e) => {
-									match e.kind() {
-										io::ErrorKind::PermissionDenied => println!("Permission denied, check permissions"),
-										_ => println!("Error saving todos: {}", e),
-									};
-								}
-							}
-							return Ok(true);
-						}
+						KeyCode::Char('q') => return Ok(true),
 						KeyCode::Char('n') => app.input_mode = InputMode::Input,
-						KeyCode::Up | KeyCode::Down => {
-							if !app.todo_list.is_empty() {
-								app.input_mode = InputMode::Select;
+						KeyCode::Char('s') => app.cycle_sort_order(),
+						KeyCode::Char('f') => app.cycle_filter(),
+						KeyCode::Char('#') => app.open_prompt(Prompt::TagFilter),
+						KeyCode::Char('%') => app.open_prompt(Prompt::FieldFilter),
+						KeyCode::Char('F') => app.open_prompt(Prompt::Fields),
+						KeyCode::Tab => app.next_list(),
+						KeyCode::BackTab => app.previous_list(),
+						KeyCode::Char('N') => app.open_prompt(Prompt::NewList),
+						KeyCode::Char('R') => app.open_prompt(Prompt::RenameList),
+						KeyCode::Char('X') => app.open_prompt(Prompt::DeleteList),
+						KeyCode::Char('P') => app.open_prompt(Prompt::PomodoroSettings),
+						KeyCode::Char('?') => app.show_keys = !app.show_keys,
+						KeyCode::Char('e') => app.show_report = !app.show_report,
+						KeyCode::Char('v') => app.toggle_view(),
+						KeyCode::Char('V') => app.cycle_board_grouping(),
+						KeyCode::Char('a') => app.open_archive(),
+						KeyCode::Char('A') => app.open_prompt(Prompt::ArchiveAfter),
+						KeyCode::Char('t') => app.open_trash(),
+						KeyCode::Char('u') => app.undo(),
+						KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => app.redo(),
+						KeyCode::Char('U') => app.redo(),
+						KeyCode::Char('T') => app.open_prompt(Prompt::TrashRetention),
+						KeyCode::Char('b') => app.open_backups(),
+						KeyCode::Char('B') => app.open_prompt(Prompt::BackupCount),
+						KeyCode::Up | KeyCode::Down if !app.visible_todos().is_empty() => {
+							if app.selected_index().is_none() {
+								app.select_todo_at(0);
 							}
+							app.input_mode = InputMode::Select;
 						}
 						_ => {}
 					},
 					InputMode::Select => match key.code {
+						KeyCode::Up if app.view == View::Board => app.move_board_selection(false),
+						KeyCode::Down if app.view == View::Board => app.move_board_selection(true),
+						KeyCode::Left if app.view == View::Board => app.switch_board_column(false),
+						KeyCode::Right if app.view == View::Board => app.switch_board_column(true),
+						KeyCode::Char('<') if app.view == View::Board => app.move_selected_card(false),
+						KeyCode::Char('>') if app.view == View::Board => app.move_selected_card(true),
 						KeyCode::Up => app.previous_todo(),
 						KeyCode::Down => app.next_todo(),
 						KeyCode::Esc | KeyCode::Char('q') => app.input_mode = InputMode::Visual,
-						KeyCode::Enter => {
-							if !app.show_todo_popup {app.show_todo_popup = true; app.input_mode = InputMode::Popup}
+						KeyCode::Enter if !app.show_todo_popup && app.selected_todo.is_some() => {
+							app.show_todo_popup = true;
+							app.show_todo_history = false;
+							app.selected_link = 0;
+							app.input_mode = InputMode::Popup;
 						}
-						KeyCode::Char('d') => {
-							app.todo_list.remove_todo(app.todo_list_index);
+						KeyCode::Char('d') => app.request_delete_selected_todo(),
+						KeyCode::Char('u') => app.undo(),
+						KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => app.redo(),
+						KeyCode::Char('U') => app.redo(),
+						KeyCode::Char('x') => app.archive_selected_todo(),
+						KeyCode::Char('o') => {
+							app.selected_link = 0;
+							app.open_selected_link();
 						}
-						KeyCode::Char('c') => {
-							app.todo_list.complete_todo(app.todo_list_index);
+						KeyCode::Char('c') => app.complete_selected_todo(false),
+						KeyCode::Char('C') => app.complete_selected_todo(true),
+						KeyCode::Char(' ') => app.cycle_selected_status(),
+						KeyCode::Char(digit @ '1'..='6') => {
+							let idx = digit as usize - '1' as usize;
+							app.set_selected_status(Status::ALL[idx]);
 						}
+						KeyCode::Char('a') => app.start_subtask(),
+						KeyCode::Left => app.collapse_selected_todo(),
+						KeyCode::Right => app.expand_selected_todo(),
+						KeyCode::Char('t') => app.open_prompt(Prompt::DueDate),
+						KeyCode::Char('+') | KeyCode::Char('=') => app.change_selected_priority(true),
+						KeyCode::Char('-') => app.change_selected_priority(false),
+						KeyCode::Char('g') => app.open_prompt(Prompt::Tags),
+						KeyCode::Char('E') => app.open_prompt(Prompt::Estimate),
+						KeyCode::Char('r') => app.open_prompt(Prompt::Recurrence),
+						KeyCode::Char('m') => app.open_prompt(Prompt::MoveToList),
+						KeyCode::Char('b') => app.open_prompt(Prompt::BlockedBy),
+						KeyCode::Char('w') => app.toggle_tracking(),
+						KeyCode::Char('p') => app.toggle_pomodoro(),
+						KeyCode::Char('#') => app.open_prompt(Prompt::TagFilter),
+						KeyCode::Char('%') => app.open_prompt(Prompt::FieldFilter),
+						KeyCode::Char('s') => app.cycle_sort_order(),
+						KeyCode::Char('f') => app.cycle_filter(),
+						KeyCode::Char('?') => app.show_keys = !app.show_keys,
+						KeyCode::Char('v') => app.toggle_view(),
+						KeyCode::Char('V') => app.cycle_board_grouping(),
 						_ => {}
 					}
 					InputMode::Input => match key.code {
-						KeyCode::Enter => {
-							if !app.input.is_empty() && !app.todo_list.contains_title(&app.input) {
-								app.submit_new_todo();
-								app.input.clear();
-								app.reset_cursor();
-								app.input_mode = InputMode::Visual;
-							}
+						KeyCode::Enter if !app.input.is_empty() && !app.is_new_todo_title_taken() => {
+							app.submit_new_todo();
+							app.input.clear();
+							app.reset_cursor();
 						}
 						KeyCode::Char(to_insert) => {
 							app.enter_char(to_insert);
@@ -60,17 +121,21 @@ pub fn handle_events(app: &mut App) -> io::Result<bool> {
 						KeyCode::Backspace => app.delete_char(),
 						KeyCode::Left => app.move_cursor_left(),
 						KeyCode::Right => app.move_cursor_right(),
-						KeyCode::Esc => {
-							app.input_mode = InputMode::Visual;
-							app.input.clear();
-							app.reset_cursor();
-						}
+						KeyCode::Esc => app.cancel_new_todo(),
 						_ => {},
 					},
 					InputMode::Popup => match key.code {
-						KeyCode::Esc => {app.input_mode = InputMode::Select; app.show_todo_popup = false}
+						KeyCode::Esc => {app.input_mode = InputMode::Select; app.show_todo_popup = false; app.show_todo_history = false}
+						KeyCode::Char('h') => app.show_todo_history = !app.show_todo_history,
+						KeyCode::Char('l') | KeyCode::Tab => app.next_link(),
+						KeyCode::Char('o') => app.open_selected_link(),
+						KeyCode::Char('E') => app.open_prompt(Prompt::Estimate),
+						KeyCode::Char('n') => app.open_prompt(Prompt::Note),
+						KeyCode::Char('e') if !app.workspace.active_list().fields().is_empty() => app.open_prompt(Prompt::EditField),
 						KeyCode::Char('i') => {
-							app.popup_input = app.todo_list.get_todo_body(app.todo_list_index).unwrap_or_else(|| " ".to_string());
+							app.popup_input = app.selected_todo
+								.and_then(|id| app.workspace.active_list().get_todo_body(id))
+								.unwrap_or_else(|| " ".to_string());
 							app.input_mode = InputMode::PopupInput;
 						}
 						_ => {}
@@ -79,24 +144,71 @@ pub fn handle_events(app: &mut App) -> io::Result<bool> {
 						KeyCode::Char(to_insert) => {
 							app.enter_char(to_insert);
 						}
-						KeyCode::Enter => {
-							if !app.popup_input.is_empty() {
-								app.todo_list.update_todo(app.todo_list_index, None, Some(app.popup_input.clone()));
-								app.popup_input.clear();
-								app.reset_cursor();
-								app.input_mode = InputMode::Select;
-								app.show_todo_popup = false;
-							}
-						}
+						KeyCode::Enter if !app.popup_input.is_empty() => app.save_popup_input(),
 						KeyCode::Backspace => app.delete_char(),
 						KeyCode::Left => app.move_cursor_left(),
 						KeyCode::Right => app.move_cursor_right(),
 						KeyCode::Esc => app.input_mode = InputMode::Popup,
 						_ => {}
 					}
+					InputMode::Archive => match key.code {
+						KeyCode::Up => app.move_archive_selection(false),
+						KeyCode::Down => app.move_archive_selection(true),
+						KeyCode::Enter => app.restore_archived(),
+						KeyCode::Char(to_insert) => {
+							app.enter_char(to_insert);
+							app.archive_state.select(Some(0));
+						}
+						KeyCode::Backspace => {
+							app.delete_char();
+							app.archive_state.select(Some(0));
+						}
+						KeyCode::Left => app.move_cursor_left(),
+						KeyCode::Right => app.move_cursor_right(),
+						KeyCode::Esc => app.close_archive(),
+						_ => {}
+					}
+					InputMode::Trash => match key.code {
+						KeyCode::Up => app.move_trash_selection(false),
+						KeyCode::Down => app.move_trash_selection(true),
+						KeyCode::Enter | KeyCode::Char('r') => app.restore_trashed(),
+						KeyCode::Delete | KeyCode::Char('p') => app.request_purge_trashed(),
+						KeyCode::Char('E') => app.request_empty_trash(),
+						KeyCode::Esc | KeyCode::Char('q') => app.input_mode = InputMode::Visual,
+						_ => {}
+					}
+					InputMode::Backups => match key.code {
+						KeyCode::Up => app.move_backup_selection(false),
+						KeyCode::Down => app.move_backup_selection(true),
+						KeyCode::Enter | KeyCode::Char('r') => app.request_restore_backup(),
+						KeyCode::Esc | KeyCode::Char('q') => app.input_mode = InputMode::Visual,
+						_ => {}
+					}
+					InputMode::Recovery => match key.code {
+						KeyCode::Up => app.move_recovery_selection(false),
+						KeyCode::Down => app.move_recovery_selection(true),
+						KeyCode::Enter => app.recover(),
+						KeyCode::Char('q') => return Ok(true),
+						_ => {}
+					}
+					InputMode::Confirm(confirm) => match key.code {
+						KeyCode::Char('y') | KeyCode::Enter => app.answer_confirmation(confirm, true),
+						KeyCode::Char('n') | KeyCode::Esc => app.answer_confirmation(confirm, false),
+						_ => {}
+					}
+					InputMode::Prompt(prompt) => match key.code {
+						KeyCode::Enter if app.submit_prompt(prompt) => app.close_prompt(),
+						KeyCode::Char(to_insert) => {
+							app.enter_char(to_insert);
+						}
+						KeyCode::Backspace => app.delete_char(),
+						KeyCode::Left => app.move_cursor_left(),
+						KeyCode::Right => app.move_cursor_right(),
+						KeyCode::Esc => app.close_prompt(),
+						_ => {}
+					}
 				}
 			}
-        }
     }
 	app.update();
     Ok(false)
@@ -0,0 +1,245 @@
+use std::cmp::Ordering;
+
+use chrono::{Local, NaiveDate};
+use serde::{Serialize, Deserialize};
+
+use crate::todo::{parse_due_date, TodoItem};
+
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum FieldKind {
+	Text,
+	Number,
+	Date,
+	Boolean,
+}
+
+impl FieldKind {
+	pub fn parse(input: &str) -> Option<Self> {
+		match input.to_lowercase().as_str() {
+			"text" => Some(FieldKind::Text),
+			"number" | "num" => Some(FieldKind::Number),
+			"date" => Some(FieldKind::Date),
+			"bool" | "boolean" => Some(FieldKind::Boolean),
+			_ => None,
+		}
+	}
+
+	pub fn label(self) -> &'static str {
+		match self {
+			FieldKind::Text => "text",
+			FieldKind::Number => "number",
+			FieldKind::Date => "date",
+			FieldKind::Boolean => "bool",
+		}
+	}
+}
+
+// a field the todos of a list can have, defined per list
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct FieldDef {
+	pub name: String,
+	pub kind: FieldKind,
+	// shown after the title in the list
+	#[serde(default)]
+	pub column: bool,
+}
+
+impl FieldDef {
+	// "ticket:text* estimate:number", a * shows the field as a column. names are unique
+	pub fn parse_all(input: &str) -> Option<Vec<FieldDef>> {
+		let mut defs: Vec<FieldDef> = Vec::new();
+		for word in input.split(|c: char| c.is_whitespace() || c == ',').filter(|word| !word.is_empty()) {
+			let (word, column) = match word.strip_suffix('*') {
+				Some(word) => (word, true),
+				None => (word, false),
+			};
+			let (name, kind) = word.split_once(':')?;
+			let name = name.trim().to_lowercase();
+			if name.is_empty() || defs.iter().any(|def| def.name == name) {
+				return None;
+			}
+			defs.push(FieldDef { name, kind: FieldKind::parse(kind)?, column });
+		}
+		Some(defs)
+	}
+
+	// written so that parse_all gives back the same fields
+	pub fn describe_all(defs: &[FieldDef]) -> String {
+		defs.iter()
+			.map(|def| format!("{}:{}{}", def.name, def.kind.label(), if def.column { "*" } else { "" }))
+			.collect::<Vec<_>>()
+			.join(" ")
+	}
+}
+
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub enum FieldValue {
+	Text(String),
+	Number(f64),
+	Date(NaiveDate),
+	Boolean(bool),
+}
+
+impl FieldValue {
+	// dates are read like due dates, booleans as yes/no, true/false, y/n or 1/0
+	pub fn parse(kind: FieldKind, input: &str) -> Option<Self> {
+		let input = input.trim();
+		match kind {
+			FieldKind::Text => Some(FieldValue::Text(input.to_string())),
+			FieldKind::Number => input.parse().ok().filter(|number: &f64| number.is_finite()).map(FieldValue::Number),
+			FieldKind::Date => parse_due_date(input).map(|date| FieldValue::Date(date.with_timezone(&Local).date_naive())),
+			FieldKind::Boolean => match input.to_lowercase().as_str() {
+				"yes" | "y" | "true" | "1" => Some(FieldValue::Boolean(true)),
+				"no" | "n" | "false" | "0" => Some(FieldValue::Boolean(false)),
+				_ => None,
+			},
+		}
+	}
+
+	pub fn display(&self) -> String {
+		match self {
+			FieldValue::Text(text) => text.clone(),
+			FieldValue::Number(number) => number.to_string(),
+			FieldValue::Date(date) => date.format("%d-%m-%Y").to_string(),
+			FieldValue::Boolean(true) => String::from("yes"),
+			FieldValue::Boolean(false) => String::from("no"),
+		}
+	}
+
+	// values of different kinds, left behind when a field changed kind, are not ordered
+	pub fn compare(&self, other: &FieldValue) -> Ordering {
+		match (self, other) {
+			(FieldValue::Text(a), FieldValue::Text(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+			(FieldValue::Number(a), FieldValue::Number(b)) => a.total_cmp(b),
+			(FieldValue::Date(a), FieldValue::Date(b)) => a.cmp(b),
+			(FieldValue::Boolean(a), FieldValue::Boolean(b)) => a.cmp(b),
+			_ => Ordering::Equal,
+		}
+	}
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum FieldOp {
+	// the todo has a value for the field
+	Has,
+	// same value, text only has to contain it
+	Equals,
+	Less,
+	Greater,
+}
+
+// shows the todos whose field compares to value
+#[derive(PartialEq, Debug, Clone)]
+pub struct FieldFilter {
+	pub name: String,
+	pub op: FieldOp,
+	pub value: String,
+}
+
+impl FieldFilter {
+	// "estimate>3", "customer=acme", "shipped=no" or just "ticket"
+	pub fn parse(input: &str) -> Option<Self> {
+		let input = input.trim();
+		let (name, op, value) = match input.find(['=', '<', '>']) {
+			Some(idx) => {
+				let op = match &input[idx..idx + 1] {
+					"=" => FieldOp::Equals,
+					"<" => FieldOp::Less,
+					_ => FieldOp::Greater,
+				};
+				(&input[..idx], op, input[idx + 1..].trim())
+			}
+			None => (input, FieldOp::Has, ""),
+		};
+		let name = name.trim().to_lowercase();
+		if name.is_empty() || (op != FieldOp::Has && value.is_empty()) {
+			return None;
+		}
+		Some(FieldFilter { name, op, value: value.to_string() })
+	}
+
+	pub fn describe(&self) -> String {
+		let op = match self.op {
+			FieldOp::Has => return self.name.clone(),
+			FieldOp::Equals => "=",
+			FieldOp::Less => "<",
+			FieldOp::Greater => ">",
+		};
+		format!("{}{}{}", self.name, op, self.value)
+	}
+
+	pub fn matches(&self, todo: &TodoItem, defs: &[FieldDef]) -> bool {
+		let Some(def) = defs.iter().find(|def| def.name == self.name) else {
+			return false;
+		};
+		let Some(value) = todo.field(&self.name) else {
+			return false;
+		};
+		if self.op == FieldOp::Has {
+			return true;
+		}
+		if let (FieldValue::Text(text), FieldOp::Equals) = (value, self.op) {
+			return text.to_lowercase().contains(&self.value.to_lowercase());
+		}
+		let Some(wanted) = FieldValue::parse(def.kind, &self.value) else {
+			return false;
+		};
+		match self.op {
+			FieldOp::Equals => value.compare(&wanted) == Ordering::Equal,
+			FieldOp::Less => value.compare(&wanted) == Ordering::Less,
+			FieldOp::Greater => value.compare(&wanted) == Ordering::Greater,
+			FieldOp::Has => true,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::todo::{IdCounter, TodoList};
+
+	fn filter(input: &str) -> FieldFilter {
+		FieldFilter::parse(input).unwrap()
+	}
+
+	#[test]
+	fn parses_filters_and_describes_them_back() {
+		assert_eq!(filter("Estimate > 3"), FieldFilter { name: String::from("estimate"), op: FieldOp::Greater, value: String::from("3") });
+		assert_eq!(filter("ticket").op, FieldOp::Has);
+		for input in ["estimate>3", "customer=acme", "due<01-02-2025", "ticket"] {
+			assert_eq!(filter(input).describe(), input);
+		}
+		for input in ["", "=acme", "estimate>", "customer= "] {
+			assert_eq!(FieldFilter::parse(input), None, "{}", input);
+		}
+	}
+
+	#[test]
+	fn matches_by_the_kind_of_the_field() {
+		let mut list = TodoList::new();
+		let mut ids = IdCounter::default();
+		list.set_fields(FieldDef::parse_all("customer:text estimate:number shipped:bool ticket:text").unwrap());
+		let id = list.add_todo(&mut ids, String::from("invoice"), None);
+		list.set_field(id, "customer", Some(FieldValue::Text(String::from("Acme Corp"))));
+		list.set_field(id, "estimate", Some(FieldValue::Number(5.0)));
+		list.set_field(id, "shipped", Some(FieldValue::Boolean(false)));
+		let todo = list.get_todo(id).unwrap();
+		let matches = |input: &str| filter(input).matches(todo, list.fields());
+		// text only has to contain the value
+		assert!(matches("customer=acme"));
+		assert!(!matches("customer=globex"));
+		// numbers compare as numbers, not as text
+		assert!(matches("estimate>3"));
+		assert!(matches("estimate<10"));
+		assert!(!matches("estimate>5"));
+		assert!(matches("estimate=5.0"));
+		assert!(matches("shipped=no"));
+		assert!(!matches("shipped=yes"));
+		assert!(matches("estimate"));
+		// a field the todo has no value for, or the list does not define, matches nothing
+		assert!(!matches("ticket"));
+		assert!(!matches("priority"));
+		// nor does a value that does not parse as the kind of the field
+		assert!(!matches("estimate>lots"));
+	}
+}
@@ -0,0 +1,229 @@
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use serde_json;
+
+use crate::archive::Archive;
+use crate::schema::{self, Kind};
+use crate::todo::{Effort, TodoList};
+use crate::trash::Trash;
+use crate::workspace::Workspace;
+use crate::storage::write_atomically;
+
+// undo steps kept, the oldest ones are dropped past this
+const HISTORY_LIMIT: usize = 50;
+// undo and redo steps written to the history file, the older ones only last until the app quits.
+// each step holds every list, so few are written and only when they changed
+const SAVED_LIMIT: usize = 5;
+
+// the lists and trash as they were before a change, with the archive only for changes that move
+// todos in or out of it. settings are not part of it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+	// what the change that followed did, shown when it is undone or redone
+	description: String,
+	lists: Vec<TodoList>,
+	active: usize,
+	trash: Trash,
+	#[serde(default)]
+	archive: Option<Archive>,
+}
+
+// the newest steps of a history, as they are written
+#[derive(Serialize)]
+struct SavedHistory<'a> {
+	undo: &'a [Snapshot],
+	redo: &'a [Snapshot],
+}
+
+impl Snapshot {
+	pub fn new(description: String, workspace: &Workspace, archive: Option<&Archive>) -> Self {
+		Snapshot {
+			description,
+			lists: workspace.lists().to_vec(),
+			active: workspace.active_index(),
+			trash: workspace.trash().clone(),
+			archive: archive.cloned(),
+		}
+	}
+
+	pub fn description(&self) -> &str {
+		&self.description
+	}
+
+	pub fn has_archive(&self) -> bool {
+		self.archive.is_some()
+	}
+
+	// puts the snapshot back in place of the current state. time tracked and pomodoros done since are kept
+	pub fn restore(self, workspace: &mut Workspace, archive: &mut Archive) {
+		let mut effort = Effort::default();
+		for list in workspace.lists() {
+			effort.collect(list.get_todos());
+		}
+		effort.collect(workspace.trash().entries().iter().map(|entry| entry.todo()));
+		effort.collect(archive.entries().iter().map(|entry| entry.todo()));
+
+		workspace.replace_lists(self.lists, self.active);
+		*workspace.trash_mut() = self.trash;
+		if let Some(saved) = self.archive {
+			*archive = saved;
+		}
+
+		for idx in 0..workspace.lists().len() {
+			if let Some(list) = workspace.list_mut(idx) {
+				list.carry_effort(&effort);
+			}
+		}
+		for entry in workspace.trash_mut().entries_mut() {
+			effort.carry_over(std::slice::from_mut(entry.todo_mut()));
+		}
+		for entry in archive.entries_mut() {
+			effort.carry_over(std::slice::from_mut(entry.todo_mut()));
+		}
+	}
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+	undo: Vec<Snapshot>,
+	redo: Vec<Snapshot>,
+	// whether there are steps the history file does not have yet
+	#[serde(skip)]
+	unsaved: bool,
+}
+
+impl History {
+	pub fn new() -> Self {
+		History { undo: Vec::new(), redo: Vec::new(), unsaved: false }
+	}
+
+	// a new change makes what was undone before unreachable
+	pub fn record(&mut self, before: Snapshot) {
+		self.undo.push(before);
+		if self.undo.len() > HISTORY_LIMIT {
+			self.undo.remove(0);
+		}
+		self.redo.clear();
+		self.unsaved = true;
+	}
+
+	// the step undo would go back to, the current state has to be taken the same way to be redone
+	pub fn next_undo(&self) -> Option<&Snapshot> {
+		self.undo.last()
+	}
+
+	pub fn next_redo(&self) -> Option<&Snapshot> {
+		self.redo.last()
+	}
+
+	// current is the state being undone, it is kept to be redone
+	pub fn undo(&mut self, mut current: Snapshot) -> Option<Snapshot> {
+		let previous = self.undo.pop()?;
+		current.description = previous.description.clone();
+		self.redo.push(current);
+		self.unsaved = true;
+		Some(previous)
+	}
+
+	pub fn redo(&mut self, mut current: Snapshot) -> Option<Snapshot> {
+		let next = self.redo.pop()?;
+		current.description = next.description.clone();
+		self.undo.push(current);
+		self.unsaved = true;
+		Some(next)
+	}
+
+	// changes that are not undo steps, like tracked time, leave the file as it is
+	pub fn save_to_file(&mut self, filename: &Path) -> std::io::Result<()> {
+		if !self.unsaved {
+			return Ok(());
+		}
+		let saved = SavedHistory {
+			undo: &self.undo[self.undo.len().saturating_sub(SAVED_LIMIT)..],
+			redo: &self.redo[self.redo.len().saturating_sub(SAVED_LIMIT)..],
+		};
+		let json = schema::to_versioned_json(&saved)?;
+		write_atomically(filename, json.as_bytes())?;
+		self.unsaved = false;
+		Ok(())
+	}
+
+	// versioned like the data file, the todos of its steps go through the same migrations
+	pub fn load_from_file(filename: &Path) -> std::io::Result<Self> {
+		let document = schema::read_migrated(filename, Kind::History)?;
+		let history = serde_json::from_value(document)?;
+		Ok(history)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn titles(workspace: &Workspace) -> Vec<&str> {
+		workspace.active_list().walk().into_iter().map(|todo| todo.title()).collect()
+	}
+
+	fn add(workspace: &mut Workspace, title: &str) -> crate::todo::TodoId {
+		let (list, ids) = workspace.active_list_and_ids();
+		list.add_todo(ids, title.to_string(), None)
+	}
+
+	#[test]
+	fn restore_puts_back_the_lists_and_keeps_tracked_time() {
+		let mut workspace = Workspace::new();
+		let mut archive = Archive::new();
+		let first = add(&mut workspace, "first");
+		let before = Snapshot::new(String::from("add 'second'"), &workspace, None);
+		add(&mut workspace, "second");
+		workspace.start_tracking(first);
+		workspace.active_list_mut().record_pomodoro(first);
+		let gone = add(&mut workspace, "archived");
+		let todo = workspace.active_list_mut().remove_todo(gone).unwrap();
+		archive.add("Todo", todo);
+
+		before.restore(&mut workspace, &mut archive);
+		assert_eq!(titles(&workspace), ["first"]);
+		let todo = workspace.active_list().get_todo(first).unwrap();
+		assert!(todo.running_interval().is_some());
+		assert_eq!(todo.pomodoros(), 1);
+		// a step without the archive leaves it as it is
+		assert_eq!(archive.len(), 1);
+	}
+
+	#[test]
+	fn restore_with_the_archive_puts_it_back() {
+		let mut workspace = Workspace::new();
+		let mut archive = Archive::new();
+		let id = add(&mut workspace, "done");
+		let before = Snapshot::new(String::from("archive 'done'"), &workspace, Some(&archive));
+		let todo = workspace.active_list_mut().remove_todo(id).unwrap();
+		archive.add("Todo", todo);
+		before.restore(&mut workspace, &mut archive);
+		assert_eq!(titles(&workspace), ["done"]);
+		assert!(archive.is_empty());
+	}
+
+	#[test]
+	fn undo_and_redo_go_back_and_forth() {
+		let mut workspace = Workspace::new();
+		let mut archive = Archive::new();
+		let mut history = History::new();
+		history.record(Snapshot::new(String::from("add 'one'"), &workspace, None));
+		add(&mut workspace, "one");
+
+		let current = Snapshot::new(String::new(), &workspace, None);
+		let previous = history.undo(current).unwrap();
+		assert_eq!(previous.description(), "add 'one'");
+		previous.restore(&mut workspace, &mut archive);
+		assert!(titles(&workspace).is_empty());
+		assert!(history.next_undo().is_none());
+
+		let current = Snapshot::new(String::new(), &workspace, None);
+		let next = history.redo(current).unwrap();
+		assert_eq!(next.description(), "add 'one'");
+		next.restore(&mut workspace, &mut archive);
+		assert_eq!(titles(&workspace), ["one"]);
+		assert!(history.next_redo().is_none());
+	}
+}
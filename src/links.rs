@@ -0,0 +1,178 @@
+use std::{env, io::{self, Write}, path::PathBuf, process::{Command, Stdio}};
+use ratatui::crossterm::{cursor::MoveTo, queue, style::{Attribute, Print, SetAttribute, SetForegroundColor}};
+use ratatui::style::{Modifier, Style};
+use serde::{Serialize, Deserialize};
+
+// a url or a file location written in the title or body of a todo
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub enum Link {
+	Url(String),
+	File { path: String, line: Option<u32> },
+}
+
+impl Link {
+	// urls start with http:// or https://, file references look like path/to/file.rs or file.rs:42
+	pub fn detect(text: &str) -> Vec<Link> {
+		let mut links: Vec<Link> = Vec::new();
+		for word in text.split_whitespace() {
+			let word = word
+				.trim_start_matches(['(', '[', '<', '"', '\'', '`'])
+				.trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '>', '"', '\'', '`']);
+			let link = if word.starts_with("http://") || word.starts_with("https://") {
+				Some(Link::Url(word.to_string()))
+			} else {
+				parse_file_reference(word)
+			};
+			if let Some(link) = link.filter(|link| !links.contains(link)) {
+				links.push(link);
+			}
+		}
+		links
+	}
+
+	pub fn label(&self) -> String {
+		match self {
+			Link::Url(url) => url.clone(),
+			Link::File { path, line: Some(line) } => format!("{}:{}", path, line),
+			Link::File { path, line: None } => path.clone(),
+		}
+	}
+
+	// what the terminal hyperlink points to, files become file:// urls
+	pub fn target(&self) -> String {
+		match self {
+			Link::Url(url) => url.clone(),
+			Link::File { path, .. } => format!("file://{}", absolute_path(path).display()),
+		}
+	}
+
+	// urls are handed to the system opener, which returns right away
+	pub fn open_url(url: &str) -> io::Result<()> {
+		let mut command = if cfg!(target_os = "macos") {
+			Command::new("open")
+		} else if cfg!(target_os = "windows") {
+			let mut command = Command::new("cmd");
+			command.args(["/C", "start", ""]);
+			command
+		} else {
+			Command::new("xdg-open")
+		};
+		command.arg(url)
+			.stdin(Stdio::null())
+			.stdout(Stdio::null())
+			.stderr(Stdio::null())
+			.spawn()?;
+		Ok(())
+	}
+
+	// $EDITOR at the referenced line, vi when it is not set. the caller gives it the terminal
+	pub fn editor_command(path: &str, line: Option<u32>) -> io::Result<Command> {
+		let editor = env::var("EDITOR").ok().filter(|editor| !editor.trim().is_empty()).unwrap_or_else(|| String::from("vi"));
+		let mut words = editor.split_whitespace();
+		let mut command = Command::new(words.next().ok_or(io::ErrorKind::NotFound)?);
+		command.args(words);
+		if let Some(line) = line {
+			command.arg(format!("+{}", line));
+		}
+		command.arg(absolute_path(path));
+		Ok(command)
+	}
+}
+
+// "src/app.rs", "./notes.md:12" or "main.rs:3". the last part needs an extension, and a bare file name a line
+fn parse_file_reference(word: &str) -> Option<Link> {
+	let (path, line) = match word.split_once(':') {
+		Some((path, rest)) => {
+			// a column after the line is dropped
+			let line = rest.split(':').next()?.parse::<u32>().ok()?;
+			(path, Some(line))
+		}
+		None => (word, None),
+	};
+	let valid_chars = path.chars().all(|c| c.is_alphanumeric() || matches!(c, '/' | '.' | '_' | '-' | '~'));
+	let file_name = path.rsplit('/').next()?;
+	let has_extension = file_name.rsplit_once('.').is_some_and(|(stem, ext)| !stem.is_empty() && !ext.is_empty() && ext.chars().all(|c| c.is_alphanumeric()));
+	if !valid_chars || !has_extension || (!path.contains('/') && line.is_none()) {
+		return None;
+	}
+	Some(Link::File { path: path.to_string(), line })
+}
+
+fn absolute_path(path: &str) -> PathBuf {
+	let path = match (path.strip_prefix("~/"), env::var_os("HOME")) {
+		(Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+		_ => PathBuf::from(path),
+	};
+	match path.is_absolute() {
+		true => path,
+		false => env::current_dir().map(|dir| dir.join(&path)).unwrap_or(path),
+	}
+}
+
+// a link as drawn on screen. ratatui gives each escape sequence a width, so the main loop writes
+// the OSC 8 sequence itself after every draw, printing the same text over what was drawn
+#[derive(Debug)]
+pub struct Hyperlink {
+	pub x: u16,
+	pub y: u16,
+	pub text: String,
+	pub style: Style,
+	pub target: String,
+}
+
+pub fn write_hyperlinks(out: &mut impl Write, hyperlinks: &[Hyperlink]) -> io::Result<()> {
+	for link in hyperlinks {
+		queue!(out, MoveTo(link.x, link.y))?;
+		if let Some(fg) = link.style.fg {
+			queue!(out, SetForegroundColor(fg.into()))?;
+		}
+		if link.style.add_modifier.contains(Modifier::UNDERLINED) {
+			queue!(out, SetAttribute(Attribute::Underlined))?;
+		}
+		queue!(out, Print(format!("\x1B]8;;{}\x07{}\x1B]8;;\x07", link.target, link.text)), SetAttribute(Attribute::Reset))?;
+	}
+	out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn file(path: &str, line: Option<u32>) -> Link {
+		Link::File { path: path.to_string(), line }
+	}
+
+	#[test]
+	fn detects_urls_without_the_punctuation_around_them() {
+		assert_eq!(
+			Link::detect("see (https://example.com/a?b=1), or <http://example.org>."),
+			[Link::Url(String::from("https://example.com/a?b=1")), Link::Url(String::from("http://example.org"))],
+		);
+		assert_eq!(Link::detect("ftp://example.com www.example.com"), []);
+	}
+
+	#[test]
+	fn detects_file_references() {
+		assert_eq!(Link::detect("fix src/app.rs first"), [file("src/app.rs", None)]);
+		assert_eq!(Link::detect("in `./notes.md:12`"), [file("./notes.md", Some(12))]);
+		// the column is dropped
+		assert_eq!(Link::detect("main.rs:3:7"), [file("main.rs", Some(3))]);
+		assert_eq!(Link::detect("~/todo/plan.txt"), [file("~/todo/plan.txt", None)]);
+	}
+
+	#[test]
+	fn ordinary_words_are_not_files() {
+		// a bare file name needs a line, a path needs an extension
+		for text in ["main.rs", "e.g. i.e.", "and/or", "src/bin", "3.5", "time: 10:30", "file.rs:line", ".hidden:3"] {
+			assert_eq!(Link::detect(text), [], "{}", text);
+		}
+	}
+
+	#[test]
+	fn each_link_is_listed_once() {
+		assert_eq!(
+			Link::detect("src/app.rs https://example.com src/app.rs https://example.com src/app.rs:4"),
+			[file("src/app.rs", None), Link::Url(String::from("https://example.com")), file("src/app.rs", Some(4))],
+		);
+	}
+}
@@ -1,36 +1,237 @@
-use std::io::{self, stdout};
+use std::{io::{self, stdout, Stdout}, panic::{self, AssertUnwindSafe}, path::PathBuf, sync::{Arc, Mutex, MutexGuard, PoisonError}, thread};
 
 pub mod timer;
 pub mod app;
+pub mod archive;
+pub mod backup;
+pub mod board;
+pub mod changelog;
+pub mod estimate;
 pub mod user_interfaces;
 pub mod todo;
+pub mod recurrence;
+pub mod schema;
+pub mod workspace;
+pub mod pomodoro;
+pub mod settings;
+pub mod status;
+pub mod storage;
+pub mod trash;
 pub mod events;
+pub mod fields;
+pub mod links;
+pub mod notes;
+pub mod paths;
+pub mod recovery;
+pub mod history;
 
-use events::handle_events;
+use events::{handle_events, next_event};
 use app::{App, State::{Startup, Display, Exit}};
 use ratatui::prelude::*;
 use ratatui::crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
+use links::{write_hyperlinks, Link};
+use paths::{DataPaths, DATA_FILE_VAR};
+use signal_hook::{consts::{SIGHUP, SIGINT, SIGTERM}, iterator::Signals};
 use user_interfaces::{startup_ui, main_ui, leave};
 
+const USAGE: &str = "usage: todo_tui [--data-file PATH] [--list-backups | --restore-backup NUMBER|NAME]";
+
+// what to do instead of starting the tui
+enum Command {
+	Run,
+	ListBackups,
+	// a number from --list-backups or the file name of the backup
+	RestoreBackup(String),
+}
+
 fn main() -> io::Result<()> {
+	let (data_file, command) = match parse_args(std::env::args().skip(1)) {
+		Ok(args) => args,
+		Err(message) => {
+			eprintln!("{}\n{}", message, USAGE);
+			std::process::exit(2);
+		}
+	};
+	let result = DataPaths::resolve(data_file).and_then(|paths| match command {
+		Command::Run => App::new(paths).map(Some),
+		Command::ListBackups => list_backups(&paths).map(|_| None),
+		Command::RestoreBackup(which) => restore_backup(&paths, &which).map(|_| None),
+	});
+	// loaded before the terminal is taken over, so that an error stays readable
+	let app = match result {
+		Ok(Some(app)) => app,
+		Ok(None) => return Ok(()),
+		Err(e) => {
+			eprintln!("Could not open the todos: {}", e);
+			std::process::exit(1);
+		}
+	};
+
+	let app = Arc::new(Mutex::new(app));
+	save_on_signals(Arc::clone(&app))?;
+
+	restore_terminal_on_panic();
+
+	let result = panic::catch_unwind(AssertUnwindSafe(|| enter_terminal().and_then(|mut terminal| run(&mut terminal, &app))));
+	// the terminal is given back before anything is printed. whether the user quit, the terminal went away
+	// or something panicked, what was not autosaved yet is saved
+	restore_terminal();
+	save_before_exit(&mut lock(&app));
+	match result {
+		Ok(result) => result,
+		Err(payload) => panic::resume_unwind(payload),
+	}
+}
+
+fn restore_terminal() {
+	let _ = disable_raw_mode();
+	let _ = stdout().execute(LeaveAlternateScreen);
+}
+
+// the panic message would be drawn over by the alternate screen and garbled by raw mode
+fn restore_terminal_on_panic() {
+	let default_hook = panic::take_hook();
+	panic::set_hook(Box::new(move |info| {
+		restore_terminal();
+		default_hook(info);
+	}));
+}
+
+fn enter_terminal() -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
     enable_raw_mode()?;
     stdout().execute(EnterAlternateScreen)?;
-    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+    Terminal::new(CrosstermBackend::new(stdout()))
+}
 
-	let mut app = App::new();
-    while !app.should_quit {
-		terminal.draw(|f| ui(f, &mut app))?;
-        app.should_quit = handle_events(&mut app)?;
+// the app is only locked while it is drawn and handles an event
+fn run(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &Mutex<App>) -> io::Result<()> {
+    while !lock(app).should_quit {
+		{
+			let mut app = lock(app);
+			terminal.draw(|f| ui(f, &mut app))?;
+			write_hyperlinks(terminal.backend_mut(), &app.hyperlinks)?;
+		}
+		let event = next_event()?;
+		let editor_request = {
+			let mut app = lock(app);
+			app.should_quit = handle_events(&mut app, event)?;
+			app.editor_request.take()
+		};
+		if let Some(Link::File { path, line }) = editor_request {
+			let edited = edit_file(terminal, &path, line);
+			let mut app = lock(app);
+			match edited {
+				Ok(true) => {}
+				Ok(false) => app.alert(format!("The editor exited with an error on {}", path)),
+				Err(e) => app.alert(format!("Could not open {} in $EDITOR: {}", path, e)),
+			}
+		}
     }
-
-    disable_raw_mode()?;
-    stdout().execute(LeaveAlternateScreen)?;
     Ok(())
 }
 
+// SIGTERM, SIGHUP and SIGINT save and end the program from their own thread. after a hangup crossterm
+// keeps reading the closed terminal and never returns to the main loop
+fn save_on_signals(app: Arc<Mutex<App>>) -> io::Result<()> {
+	let mut signals = Signals::new([SIGTERM, SIGHUP, SIGINT])?;
+	thread::spawn(move || {
+		if let Some(signal) = signals.forever().next() {
+			// held from here on so the main loop can't draw again
+			let mut app = lock(&app);
+			restore_terminal();
+			save_before_exit(&mut app);
+			std::process::exit(128 + signal);
+		}
+	});
+	Ok(())
+}
+
+// stops the clock and saves, only once the terminal is given back so the messages can be read
+fn save_before_exit(app: &mut App) {
+	app.stop_tracking();
+	match app.save() {
+		Ok(()) => println!("Saved the todos to {}", app.paths.data_file().display()),
+		Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+			eprintln!("Could not save the todos, check the permissions of {}: {}", app.paths.data_file().display(), e)
+		}
+		Err(e) => eprintln!("Could not save the todos: {}", e),
+	}
+}
+
+// a panic while the app was locked leaves it as it was, it can still be saved
+fn lock(app: &Mutex<App>) -> MutexGuard<'_, App> {
+	app.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// the data file given with --data-file PATH or --data-file=PATH, and the command
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<(Option<PathBuf>, Command), String> {
+	let mut data_file = None;
+	let mut command = Command::Run;
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"--data-file" => data_file = Some(PathBuf::from(args.next().ok_or("--data-file needs a path")?)),
+			"--list-backups" => command = Command::ListBackups,
+			"--restore-backup" => command = Command::RestoreBackup(args.next().ok_or("--restore-backup needs a backup number or name")?),
+			"-h" | "--help" => {
+				println!("{}\nthe data file can also be set with ${}", USAGE, DATA_FILE_VAR);
+				std::process::exit(0);
+			}
+			_ => match arg.strip_prefix("--data-file=") {
+				Some(path) => data_file = Some(PathBuf::from(path)),
+				None => return Err(format!("unknown argument '{}'", arg)),
+			},
+		}
+	}
+	Ok((data_file, command))
+}
+
+fn list_backups(paths: &DataPaths) -> io::Result<()> {
+	let backups = backup::list(paths)?;
+	if backups.is_empty() {
+		println!("No backups of {} yet", paths.data_file().display());
+	}
+	for (idx, backup) in backups.iter().enumerate() {
+		println!(
+			"{:>3}  {}  {:>9}  {}",
+			idx + 1,
+			backup.taken_at().format("%d-%m-%Y %H:%M:%S"),
+			backup::format_size(backup.size()),
+			backup.path().display()
+		);
+	}
+	Ok(())
+}
+
+// the data file as it was is backed up first. nothing is pruned, the next start does that with the kept count
+fn restore_backup(paths: &DataPaths, which: &str) -> io::Result<()> {
+	let backups = backup::list(paths)?;
+	let chosen = match which.parse::<usize>() {
+		Ok(number) => number.checked_sub(1).and_then(|idx| backups.get(idx)),
+		Err(_) => backups.iter().find(|backup| backup.file_name() == which),
+	};
+	let Some(chosen) = chosen else {
+		return Err(io::Error::new(io::ErrorKind::NotFound, format!("no backup '{}', see --list-backups", which)));
+	};
+	backup::restore(paths, chosen, usize::MAX)?;
+	println!("Restored {} from {}", paths.data_file().display(), chosen.path().display());
+	Ok(())
+}
+
+// leaves the tui for the editor and takes the terminal back once it exits, returns whether the editor succeeded
+fn edit_file(terminal: &mut Terminal<CrosstermBackend<Stdout>>, path: &str, line: Option<u32>) -> io::Result<bool> {
+	disable_raw_mode()?;
+	stdout().execute(LeaveAlternateScreen)?;
+	let status = Link::editor_command(path, line).and_then(|mut command| command.status());
+	stdout().execute(EnterAlternateScreen)?;
+	enable_raw_mode()?;
+	// the editor drew over the screen, the next frame is drawn in full
+	terminal.clear()?;
+	Ok(status?.success())
+}
+
 fn ui(frame: &mut Frame, app: &mut App) {
 	match app.state {
 		Startup => startup_ui(frame),
@@ -0,0 +1,23 @@
+use chrono::{DateTime, Local, Utc};
+use serde::{Serialize, Deserialize};
+
+// a progress update or finding on a todo. notes are only ever added, unlike the body
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Note {
+	text: String,
+	#[serde(with = "chrono::serde::ts_seconds")]
+	at: DateTime<Utc>,
+}
+
+impl Note {
+	pub fn now(text: String) -> Self {
+		Note { text, at: Utc::now() }
+	}
+
+	pub fn text(&self) -> &str {
+		&self.text
+	}
+	pub fn at(&self) -> DateTime<Local> {
+		self.at.with_timezone(&chrono::Local)
+	}
+}
@@ -0,0 +1,175 @@
+use std::{env, ffi::OsString, fs, io, path::{Path, PathBuf}};
+
+// set to use another data file, the --data-file flag wins over it
+pub const DATA_FILE_VAR: &str = "TODO_TUI_DATA_FILE";
+// where versions before the data directory kept their files, relative to where they were started
+const LEGACY_DATA_FILE: &str = ".todo_temp.json";
+const LEGACY_ARCHIVE_FILE: &str = ".todo_archive.json";
+const LEGACY_HISTORY_FILE: &str = ".todo_history.json";
+
+// the data file, with the archive and undo history next to it and named after it
+#[derive(Debug, Clone)]
+pub struct DataPaths {
+	data_file: PathBuf,
+	data_stem: String,
+	archive_file: PathBuf,
+	history_file: PathBuf,
+	backup_dir: PathBuf,
+}
+
+impl DataPaths {
+	pub fn new(data_file: PathBuf) -> Self {
+		let data_stem = data_file.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+		let sibling = |suffix: &str| data_file.with_file_name(format!("{}.{}.json", data_stem, suffix));
+		DataPaths {
+			archive_file: sibling("archive"),
+			history_file: sibling("history"),
+			backup_dir: data_file.with_file_name("backups"),
+			data_file,
+			data_stem,
+		}
+	}
+
+	// the flag, then $TODO_TUI_DATA_FILE, then todos.json in $XDG_DATA_HOME/todo_tui or ~/.local/share/todo_tui.
+	// the files of the default location are moved in from the current directory the first time
+	pub fn resolve(data_file_flag: Option<PathBuf>) -> io::Result<Self> {
+		let data_file = data_file_flag.or_else(|| env::var_os(DATA_FILE_VAR).filter(|var| !var.is_empty()).map(PathBuf::from));
+		Self::resolve_in(data_file, || default_data_dir(env::var_os("XDG_DATA_HOME"), env::var_os("HOME")), Path::new(""))
+	}
+
+	// the legacy files are looked for in legacy_dir, which is the current directory outside of tests
+	fn resolve_in(data_file: Option<PathBuf>, default_dir: impl FnOnce() -> io::Result<PathBuf>, legacy_dir: &Path) -> io::Result<Self> {
+		let is_default = data_file.is_none();
+		let paths = match data_file {
+			Some(data_file) => DataPaths::new(data_file),
+			None => DataPaths::new(default_dir()?.join("todos.json")),
+		};
+		paths.create_dir()?;
+		if is_default {
+			paths.move_legacy_files(legacy_dir)?;
+		}
+		Ok(paths)
+	}
+
+	pub fn data_file(&self) -> &Path {
+		&self.data_file
+	}
+	pub fn archive_file(&self) -> &Path {
+		&self.archive_file
+	}
+	pub fn history_file(&self) -> &Path {
+		&self.history_file
+	}
+	// shared by the data files of a directory, backups start with the name of their data file
+	pub fn backup_dir(&self) -> &Path {
+		&self.backup_dir
+	}
+	pub fn data_stem(&self) -> &str {
+		&self.data_stem
+	}
+
+	fn create_dir(&self) -> io::Result<()> {
+		match self.data_file.parent() {
+			Some(dir) if !dir.as_os_str().is_empty() => fs::create_dir_all(dir),
+			_ => Ok(()),
+		}
+	}
+
+	// only when there is no data file yet, so it happens once and never overwrites anything
+	fn move_legacy_files(&self, legacy_dir: &Path) -> io::Result<()> {
+		if self.data_file.try_exists()? || !legacy_dir.join(LEGACY_DATA_FILE).try_exists()? {
+			return Ok(());
+		}
+		for (from, to) in [
+			(LEGACY_ARCHIVE_FILE, &self.archive_file),
+			(LEGACY_HISTORY_FILE, &self.history_file),
+			(LEGACY_DATA_FILE, &self.data_file),
+		] {
+			let from = legacy_dir.join(from);
+			if from.try_exists()? && !to.try_exists()? {
+				move_file(&from, to)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+// from $XDG_DATA_HOME and $HOME
+fn default_data_dir(xdg_data_home: Option<OsString>, home: Option<OsString>) -> io::Result<PathBuf> {
+	// the spec says relative paths in XDG variables are to be ignored
+	let data_home = xdg_data_home
+		.map(PathBuf::from)
+		.filter(|dir| dir.is_absolute())
+		.or_else(|| home.filter(|home| !home.is_empty()).map(|home| PathBuf::from(home).join(".local/share")))
+		.ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("neither XDG_DATA_HOME nor HOME is set, set {} to pick a data file", DATA_FILE_VAR)))?;
+	Ok(data_home.join("todo_tui"))
+}
+
+// rename does not work across file systems, the data directory can be on another one
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+	if fs::rename(from, to).is_err() {
+		fs::copy(from, to)?;
+		fs::remove_file(from)?;
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::storage::tests::scratch_dir;
+
+	#[test]
+	fn files_are_named_after_the_data_file() {
+		let dir = scratch_dir("paths_named");
+		let paths = DataPaths::resolve(Some(dir.join("nested/work.json"))).unwrap();
+		assert!(dir.join("nested").is_dir());
+		assert_eq!(paths.data_stem(), "work");
+		assert_eq!(paths.archive_file(), dir.join("nested/work.archive.json"));
+		assert_eq!(paths.history_file(), dir.join("nested/work.history.json"));
+		assert_eq!(paths.backup_dir(), dir.join("nested/backups"));
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn default_directory_follows_xdg() {
+		let dir = |xdg: Option<&str>, home: Option<&str>| default_data_dir(xdg.map(OsString::from), home.map(OsString::from));
+		assert_eq!(dir(Some("/data"), Some("/home/me")).unwrap(), Path::new("/data/todo_tui"));
+		assert_eq!(dir(Some("data"), Some("/home/me")).unwrap(), Path::new("/home/me/.local/share/todo_tui"));
+		assert_eq!(dir(None, Some("/home/me")).unwrap(), Path::new("/home/me/.local/share/todo_tui"));
+		assert_eq!(dir(None, Some("")).unwrap_err().kind(), io::ErrorKind::NotFound);
+		assert_eq!(dir(Some("data"), None).unwrap_err().kind(), io::ErrorKind::NotFound);
+	}
+
+	#[test]
+	fn legacy_files_are_moved_into_the_default_location_once() {
+		let dir = scratch_dir("paths_legacy");
+		let (legacy_dir, data_dir) = (dir.join("project"), dir.join("share/todo_tui"));
+		fs::create_dir_all(&legacy_dir).unwrap();
+		for name in [LEGACY_DATA_FILE, LEGACY_ARCHIVE_FILE, LEGACY_HISTORY_FILE] {
+			fs::write(legacy_dir.join(name), name).unwrap();
+		}
+		let paths = DataPaths::resolve_in(None, || Ok(data_dir.clone()), &legacy_dir).unwrap();
+		assert_eq!(paths.data_file(), data_dir.join("todos.json"));
+		assert_eq!(fs::read_to_string(paths.data_file()).unwrap(), LEGACY_DATA_FILE);
+		assert_eq!(fs::read_to_string(paths.archive_file()).unwrap(), LEGACY_ARCHIVE_FILE);
+		assert_eq!(fs::read_to_string(paths.history_file()).unwrap(), LEGACY_HISTORY_FILE);
+		assert!(!legacy_dir.join(LEGACY_DATA_FILE).exists());
+		// a data file that is already there is never written over
+		fs::write(legacy_dir.join(LEGACY_DATA_FILE), "newer").unwrap();
+		DataPaths::resolve_in(None, || Ok(data_dir.clone()), &legacy_dir).unwrap();
+		assert_eq!(fs::read_to_string(paths.data_file()).unwrap(), LEGACY_DATA_FILE);
+		assert!(legacy_dir.join(LEGACY_DATA_FILE).exists());
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn chosen_data_file_leaves_legacy_files_alone() {
+		let dir = scratch_dir("paths_chosen");
+		fs::write(dir.join(LEGACY_DATA_FILE), "legacy").unwrap();
+		let paths = DataPaths::resolve_in(Some(dir.join("todos.json")), || unreachable!(), &dir).unwrap();
+		assert!(!paths.data_file().exists());
+		assert!(dir.join(LEGACY_DATA_FILE).exists());
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
@@ -0,0 +1,141 @@
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+
+use crate::timer::Timer;
+use crate::todo::TodoId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PomodoroSettings {
+	pub focus_minutes: u64,
+	pub short_break_minutes: u64,
+	pub long_break_minutes: u64,
+	// number of focus sessions before a break is a long one
+	pub sessions_before_long_break: u32,
+}
+
+impl Default for PomodoroSettings {
+	fn default() -> Self {
+		PomodoroSettings {
+			focus_minutes: 25,
+			short_break_minutes: 5,
+			long_break_minutes: 15,
+			sessions_before_long_break: 4,
+		}
+	}
+}
+
+impl PomodoroSettings {
+	// "focus/short/long/sessions" in minutes, like 25/5/15/4
+	pub fn parse(input: &str) -> Option<Self> {
+		let numbers: Vec<u64> = input
+			.split(|c: char| c == '/' || c.is_whitespace())
+			.filter(|part| !part.is_empty())
+			.map(|part| part.parse().ok().filter(|&number| number > 0))
+			.collect::<Option<_>>()?;
+		match numbers[..] {
+			[focus, short, long, sessions] => Some(PomodoroSettings {
+				focus_minutes: focus,
+				short_break_minutes: short,
+				long_break_minutes: long,
+				sessions_before_long_break: u32::try_from(sessions).ok()?,
+			}),
+			_ => None,
+		}
+	}
+
+	pub fn describe(&self) -> String {
+		format!(
+			"{}/{}/{}/{}",
+			self.focus_minutes, self.short_break_minutes, self.long_break_minutes, self.sessions_before_long_break
+		)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+	Focus,
+	ShortBreak,
+	LongBreak,
+}
+
+impl Phase {
+	pub fn label(self) -> &'static str {
+		match self {
+			Phase::Focus => "Focus",
+			Phase::ShortBreak => "Short break",
+			Phase::LongBreak => "Long break",
+		}
+	}
+}
+
+// a running cycle of focus sessions and breaks for one todo
+#[derive(Debug)]
+pub struct Pomodoro {
+	pub list: usize,
+	pub todo: TodoId,
+	phase: Phase,
+	timer: Timer,
+	focus_sessions: u32,
+	settings: PomodoroSettings,
+}
+
+impl Pomodoro {
+	pub fn new(list: usize, todo: TodoId, settings: PomodoroSettings) -> Self {
+		Pomodoro {
+			list,
+			todo,
+			phase: Phase::Focus,
+			timer: Timer::new(settings.focus_minutes * 60),
+			focus_sessions: 0,
+			settings,
+		}
+	}
+
+	pub fn phase(&self) -> Phase {
+		self.phase
+	}
+
+	pub fn remaining(&self) -> Duration {
+		self.timer.remaining()
+	}
+
+	// focus sessions finished since this pomodoro was started
+	pub fn focus_sessions(&self) -> u32 {
+		self.focus_sessions
+	}
+
+	pub fn sessions_before_long_break(&self) -> u32 {
+		self.settings.sessions_before_long_break
+	}
+
+	// share of the current phase that is already over, for the gauge
+	pub fn progress(&self) -> f64 {
+		let total = self.timer.duration().as_secs_f64();
+		if total == 0.0 {
+			return 1.0;
+		}
+		(1.0 - self.timer.remaining().as_secs_f64() / total).clamp(0.0, 1.0)
+	}
+
+	// moves on to the next phase once the current one is over and returns the phase that just ended
+	pub fn tick(&mut self) -> Option<Phase> {
+		if !self.timer.is_elapsed() {
+			return None;
+		}
+		let ended = self.phase;
+		let (next, minutes) = match ended {
+			Phase::Focus => {
+				self.focus_sessions += 1;
+				if self.focus_sessions.is_multiple_of(self.settings.sessions_before_long_break.max(1)) {
+					(Phase::LongBreak, self.settings.long_break_minutes)
+				} else {
+					(Phase::ShortBreak, self.settings.short_break_minutes)
+				}
+			}
+			Phase::ShortBreak | Phase::LongBreak => (Phase::Focus, self.settings.focus_minutes),
+		};
+		self.phase = next;
+		self.timer = Timer::new(minutes * 60);
+		Some(ended)
+	}
+}
@@ -0,0 +1,264 @@
+use std::{fs, io, path::{Path, PathBuf}};
+use chrono::Local;
+use serde_json::Value;
+
+use crate::backup::{self, Backup};
+use crate::paths::DataPaths;
+use crate::schema;
+use crate::settings::Settings;
+use crate::todo::{IdCounter, TodoItem, TodoList};
+use crate::workspace::Workspace;
+
+// characters shown on each side of where reading stopped, the data file is a single line
+const EXCERPT_RADIUS: usize = 30;
+// the list the todos found in a file that is not even valid json end up in
+const RECOVERED_LIST_NAME: &str = "Recovered";
+
+// a data file that could not be read, moved out of the way until the user picks what to continue with
+#[derive(Debug)]
+pub struct Recovery {
+	moved_to: PathBuf,
+	error: String,
+	// the text around where reading stopped, and how far into it that is
+	excerpt: Option<(String, usize)>,
+	salvaged: Option<Workspace>,
+	backups: Vec<Backup>,
+}
+
+// what the user can continue with, in the order they are offered
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Choice {
+	Salvaged,
+	Backup(usize),
+	Fresh,
+}
+
+impl Recovery {
+	// moves the data file aside as <stem>.corrupt-<time>.json so nothing is written over it while the
+	// user picks, then salvages what it can from it
+	pub fn start(paths: &DataPaths, error: &io::Error) -> io::Result<Self> {
+		let contents = fs::read(paths.data_file())?;
+		let text = String::from_utf8_lossy(&contents);
+		let moved_to = move_aside(paths.data_file())?;
+		let located = locate(&text, error);
+		Ok(Recovery {
+			moved_to,
+			excerpt: located.as_ref().and_then(|&(_, line, column)| excerpt(&text, line, column)),
+			error: located.map_or_else(|| error.to_string(), |(message, _, _)| message),
+			salvaged: salvage(&text),
+			// a broken backup directory only means there is nothing to offer from it
+			backups: backup::list(paths).unwrap_or_default(),
+		})
+	}
+
+	pub fn moved_to(&self) -> &Path {
+		&self.moved_to
+	}
+	// serde_json says where it stopped: "expected `,` or `}` at line 1 column 5120"
+	pub fn error(&self) -> &str {
+		&self.error
+	}
+	pub fn excerpt(&self) -> Option<(&str, usize)> {
+		self.excerpt.as_ref().map(|(text, offset)| (text.as_str(), *offset))
+	}
+	pub fn salvaged(&self) -> Option<&Workspace> {
+		self.salvaged.as_ref()
+	}
+	pub fn backups(&self) -> &[Backup] {
+		&self.backups
+	}
+
+	pub fn choices(&self) -> Vec<Choice> {
+		let mut choices = Vec::new();
+		if self.salvaged.is_some() {
+			choices.push(Choice::Salvaged);
+		}
+		choices.extend((0..self.backups.len()).map(Choice::Backup));
+		choices.push(Choice::Fresh);
+		choices
+	}
+
+	pub fn take_salvaged(&mut self) -> Option<Workspace> {
+		self.salvaged.take()
+	}
+
+	// moves the broken file back when nothing was picked, the next start offers to recover it again
+	pub fn put_back(&self, paths: &DataPaths) -> io::Result<()> {
+		if self.moved_to.try_exists()? && !paths.data_file().try_exists()? {
+			fs::rename(&self.moved_to, paths.data_file())?;
+		}
+		Ok(())
+	}
+}
+
+// renames a file that could not be read to <stem>.corrupt-<time>.json next to it, nothing writes over it there
+pub fn move_aside(path: &Path) -> io::Result<PathBuf> {
+	let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+	let moved_to = path.with_file_name(format!("{}.corrupt-{}.json", stem, Local::now().format("%Y%m%d-%H%M%S")));
+	fs::rename(path, &moved_to)?;
+	Ok(moved_to)
+}
+
+// errors that mean the file is there and readable but its contents are broken, anything else is not recovered from
+pub fn is_corrupt(error: &io::Error) -> bool {
+	matches!(error.kind(), io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof)
+}
+
+pub fn todo_count(workspace: &Workspace) -> usize {
+	workspace.lists().iter().map(TodoList::len).sum()
+}
+
+// the serde_json error with the line and column it stopped at. errors from reading the migrated document have none,
+// reading the text straight gives them when it fails the same way, as files of the current version do
+fn locate(text: &str, error: &io::Error) -> Option<(String, usize, usize)> {
+	let json_error = error.get_ref()?.downcast_ref::<serde_json::Error>()?;
+	let located = match json_error.line() {
+		0 => serde_json::from_str::<Workspace>(text).err().filter(|direct| direct.to_string().starts_with(&json_error.to_string()))?,
+		_ => return Some((json_error.to_string(), json_error.line(), json_error.column())),
+	};
+	Some((located.to_string(), located.line(), located.column()))
+}
+
+// the todos that still read on their own. when the file is valid json they keep their list,
+// otherwise every object in the text that reads as a todo is put in one list
+fn salvage(text: &str) -> Option<Workspace> {
+	let mut workspace = Workspace::new();
+	let mut lists = match serde_json::from_str::<Value>(text) {
+		Ok(mut document) => {
+			// best effort, a version that is not a number is left for the todos to fail on
+			let _ = schema::migrate(&mut document, schema::Kind::Workspace);
+			if let Some(settings) = document.get("settings").and_then(|settings| serde_json::from_value::<Settings>(settings.clone()).ok()) {
+				*workspace.settings_mut() = settings;
+			}
+			lists_from_document(&document)
+		}
+		Err(_) => Vec::new(),
+	};
+	if lists.iter().all(TodoList::is_empty) {
+		let todos = todos_in_text(text);
+		lists = vec![list_of(RECOVERED_LIST_NAME, todos, false)];
+	}
+	if lists.iter().all(TodoList::is_empty) {
+		return None;
+	}
+	workspace.replace_lists(lists, 0);
+	Some(workspace)
+}
+
+fn lists_from_document(document: &Value) -> Vec<TodoList> {
+	let Some(lists) = document.get("lists").and_then(Value::as_array) else {
+		return Vec::new();
+	};
+	lists.iter()
+		.map(|list| {
+			let name = list.get("name").and_then(Value::as_str).unwrap_or(RECOVERED_LIST_NAME);
+			let todos = list.get("todos")
+				.and_then(Value::as_array)
+				.map(|todos| todos.iter().filter_map(|todo| serde_json::from_value::<TodoItem>(todo.clone()).ok()).collect())
+				.unwrap_or_default();
+			list_of(name, todos, true)
+		})
+		.collect()
+}
+
+// tries every '{' as the start of a todo, a todo that reads is taken with its subtasks and skipped over
+fn todos_in_text(text: &str) -> Vec<TodoItem> {
+	let mut todos = Vec::new();
+	let mut start = 0;
+	while let Some(found) = text[start..].find('{') {
+		let at = start + found;
+		let mut stream = serde_json::Deserializer::from_str(&text[at..]).into_iter::<TodoItem>();
+		match stream.next() {
+			Some(Ok(todo)) => {
+				todos.push(todo);
+				start = at + stream.byte_offset();
+			}
+			_ => start = at + 1,
+		}
+	}
+	todos
+}
+
+// todos found anywhere in the text may come from different lists, their blockers are not kept.
+// ids shared with the other lists are made unique once the lists are in the workspace
+fn list_of(name: &str, todos: Vec<TodoItem>, same_list: bool) -> TodoList {
+	let mut list = TodoList::with_name(name.to_string());
+	let mut ids = IdCounter::default();
+	ids.skip_past(TodoList::max_id_in(&todos));
+	// inserting puts each on top, the last goes in first to keep the order
+	for todo in todos.into_iter().rev() {
+		match same_list {
+			true => list.restore_todo(&mut ids, todo),
+			false => list.insert_todo(&mut ids, todo),
+		};
+	}
+	list.refresh_links();
+	list
+}
+
+// the part of the line around the column serde_json reported, with the offset of that column in it.
+// the column counts bytes from 1, the excerpt is cut by characters
+fn excerpt(text: &str, line: usize, column: usize) -> Option<(String, usize)> {
+	let line = text.lines().nth(line.checked_sub(1)?)?;
+	let mut byte = column.saturating_sub(1).min(line.len());
+	while !line.is_char_boundary(byte) {
+		byte -= 1;
+	}
+	let at = line[..byte].chars().count();
+	let chars: Vec<char> = line.chars().collect();
+	let from = at.saturating_sub(EXCERPT_RADIUS);
+	let to = (at + EXCERPT_RADIUS).min(chars.len());
+	Some((chars[from..to].iter().collect(), at - from))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	fn todo(id: u64, title: &str) -> Value {
+		json!({ "id": id, "title": title, "body": null, "creation_date": 1700000000, "last_edit_date": 1700000000 })
+	}
+
+	#[test]
+	fn truncated_file_keeps_the_todos_that_read() {
+		let document = json!({
+			"version": schema::SCHEMA_VERSION,
+			"lists": [
+				{ "name": "Work", "todos": [todo(1, "first"), todo(2, "second")] },
+				{ "name": "Home", "todos": [todo(1, "third")] },
+			],
+		});
+		let text = document.to_string();
+		// cut in the middle of the third todo
+		let cut = text.find("third").unwrap();
+		let salvaged = salvage(&text[..cut]).unwrap();
+		// the text is not json anymore, the todos are gathered in one list in the order they were found
+		assert_eq!(salvaged.lists().len(), 1);
+		let list = &salvaged.lists()[0];
+		assert_eq!(list.name(), RECOVERED_LIST_NAME);
+		let titles: Vec<&str> = list.get_todos().iter().map(TodoItem::title).collect();
+		assert_eq!(titles, ["first", "second"]);
+	}
+
+	#[test]
+	fn valid_json_keeps_its_lists() {
+		let document = json!({
+			"version": schema::SCHEMA_VERSION,
+			"lists": [
+				{ "name": "Work", "todos": [todo(1, "first"), { "id": 2, "title": "no dates" }] },
+				{ "name": "Home", "todos": [todo(1, "third")] },
+			],
+		});
+		let salvaged = salvage(&document.to_string()).unwrap();
+		let names: Vec<&str> = salvaged.lists().iter().map(TodoList::name).collect();
+		assert_eq!(names, ["Work", "Home"]);
+		assert_eq!(todo_count(&salvaged), 2);
+	}
+
+	#[test]
+	fn nothing_readable_salvages_nothing() {
+		assert!(salvage("{\"version\":2,\"lists\":[{\"name\":\"Work\",\"todos\":[{\"id\":1,\"tit").is_none());
+		assert!(salvage("").is_none());
+	}
+}
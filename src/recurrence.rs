@@ -0,0 +1,187 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Recurrence {
+	Daily,
+	Weekly(Vec<Weekday>),
+	Monthly(u32),
+	// counted from the day the previous occurrence was completed, not from its due date
+	AfterCompletion(u32),
+}
+
+const WEEKDAYS: [Weekday; 7] = [Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun];
+
+fn weekday_name(day: Weekday) -> &'static str {
+	match day {
+		Weekday::Mon => "mon",
+		Weekday::Tue => "tue",
+		Weekday::Wed => "wed",
+		Weekday::Thu => "thu",
+		Weekday::Fri => "fri",
+		Weekday::Sat => "sat",
+		Weekday::Sun => "sun",
+	}
+}
+
+fn parse_weekday(input: &str) -> Option<Weekday> {
+	WEEKDAYS.into_iter().find(|&day| input.starts_with(weekday_name(day)))
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+	let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+	NaiveDate::from_ymd_opt(next_year, next_month, 1)
+		.and_then(|first| first.pred_opt())
+		.map(|last| last.day())
+		.unwrap_or(28)
+}
+
+impl Recurrence {
+	// "daily", "weekly mon,thu", "monthly 15" or "every 3", "every 3 days", "every 1 day" (days after completion)
+	pub fn parse(input: &str) -> Option<Self> {
+		let input = input.trim().to_lowercase();
+		let mut words = input.splitn(2, char::is_whitespace);
+		let kind = words.next()?;
+		let rest = words.next().unwrap_or("").trim();
+		match kind {
+			"daily" if rest.is_empty() => Some(Recurrence::Daily),
+			"weekly" => {
+				let mut days: Vec<Weekday> = rest
+					.split(|c: char| c.is_whitespace() || c == ',')
+					.filter(|day| !day.is_empty())
+					.map(parse_weekday)
+					.collect::<Option<_>>()?;
+				if days.is_empty() {
+					days.push(Local::now().weekday());
+				}
+				days.sort_by_key(|day| day.num_days_from_monday());
+				days.dedup();
+				Some(Recurrence::Weekly(days))
+			}
+			"monthly" => match rest.parse() {
+				Ok(day @ 1..=31) => Some(Recurrence::Monthly(day)),
+				_ => None,
+			},
+			"every" => match ["days", "day", "d"].iter().find_map(|unit| rest.strip_suffix(unit)).unwrap_or(rest).trim().parse() {
+				Ok(days) if days > 0 => Some(Recurrence::AfterCompletion(days)),
+				_ => None,
+			},
+			_ => None,
+		}
+	}
+
+	// written so that parse gives back the same rule
+	pub fn describe(&self) -> String {
+		match self {
+			Recurrence::Daily => String::from("daily"),
+			Recurrence::Weekly(days) => format!(
+				"weekly {}",
+				days.iter().map(|&day| weekday_name(day)).collect::<Vec<_>>().join(",")
+			),
+			Recurrence::Monthly(day) => format!("monthly {}", day),
+			Recurrence::AfterCompletion(days) => format!("every {}", days),
+		}
+	}
+
+	// the due date of the occurrence that follows one completed at completed_at.
+	// fixed schedules continue from the later of the old due date and today, so an occurrence
+	// completed late does not spawn one that is already overdue. the time of day is kept
+	pub fn next_due(&self, due: Option<DateTime<Utc>>, completed_at: DateTime<Utc>) -> Option<DateTime<Utc>> {
+		let completed_at = completed_at.with_timezone(&Local);
+		let due = due.map(|due| due.with_timezone(&Local));
+		let time = due.map(|due| due.time()).or_else(|| NaiveTime::from_hms_opt(23, 59, 59))?;
+		let reference = match due {
+			Some(due) if due.date_naive() > completed_at.date_naive() => due.date_naive(),
+			_ => completed_at.date_naive(),
+		};
+		let next_date = match self {
+			Recurrence::Daily => reference + Duration::days(1),
+			Recurrence::Weekly(days) => (1..=7)
+				.map(|offset| reference + Duration::days(offset))
+				.find(|date| days.contains(&date.weekday()))?,
+			Recurrence::Monthly(day) => {
+				let this_month = reference.with_day(1)?;
+				let candidate = this_month.with_day((*day).min(days_in_month(this_month.year(), this_month.month())))?;
+				if candidate > reference {
+					candidate
+				} else {
+					let next_month = this_month + Duration::days(days_in_month(this_month.year(), this_month.month()).into());
+					next_month.with_day((*day).min(days_in_month(next_month.year(), next_month.month())))?
+				}
+			}
+			Recurrence::AfterCompletion(days) => completed_at.date_naive() + Duration::days((*days).into()),
+		};
+		Local.from_local_datetime(&next_date.and_time(time)).earliest().map(|date| date.with_timezone(&Utc))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn at(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+		Local.with_ymd_and_hms(year, month, day, 18, 0, 0).unwrap().with_timezone(&Utc)
+	}
+
+	fn date_of(due: Option<DateTime<Utc>>) -> NaiveDate {
+		due.unwrap().with_timezone(&Local).date_naive()
+	}
+
+	#[test]
+	fn parses_days_after_completion_with_or_without_a_unit() {
+		for input in ["every 3", "every 3d", "every 3 day", "every 3 days", "Every 3 Days"] {
+			assert_eq!(Recurrence::parse(input), Some(Recurrence::AfterCompletion(3)), "{}", input);
+		}
+		assert_eq!(Recurrence::parse("every 1 day"), Some(Recurrence::AfterCompletion(1)));
+		for input in ["every", "every 0 days", "every days", "every 3 weeks"] {
+			assert_eq!(Recurrence::parse(input), None, "{}", input);
+		}
+	}
+
+	#[test]
+	fn describe_parses_back() {
+		for recurrence in [
+			Recurrence::Daily,
+			Recurrence::Weekly(vec![Weekday::Mon, Weekday::Thu]),
+			Recurrence::Monthly(31),
+			Recurrence::AfterCompletion(3),
+		] {
+			assert_eq!(Recurrence::parse(&recurrence.describe()), Some(recurrence));
+		}
+	}
+
+	#[test]
+	fn monthly_rolls_over_to_the_end_of_a_shorter_month() {
+		let next = Recurrence::Monthly(31).next_due(Some(at(2025, 1, 31)), at(2025, 1, 31));
+		assert_eq!(date_of(next), NaiveDate::from_ymd_opt(2025, 2, 28).unwrap());
+		let next = Recurrence::Monthly(31).next_due(Some(at(2024, 1, 31)), at(2024, 1, 31));
+		assert_eq!(date_of(next), NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+		// and back to the 31st after that
+		let next = Recurrence::Monthly(31).next_due(Some(at(2025, 2, 28)), at(2025, 2, 28));
+		assert_eq!(date_of(next), NaiveDate::from_ymd_opt(2025, 3, 31).unwrap());
+		let next = Recurrence::Daily.next_due(Some(at(2025, 12, 31)), at(2025, 12, 31));
+		assert_eq!(date_of(next), NaiveDate::from_ymd_opt(2026, 1, 1).unwrap());
+	}
+
+	#[test]
+	fn overdue_occurrence_is_followed_by_one_that_is_not() {
+		let (due, completed_at) = (at(2025, 3, 3), at(2025, 3, 12));
+		let next = Recurrence::Daily.next_due(Some(due), completed_at);
+		assert_eq!(date_of(next), NaiveDate::from_ymd_opt(2025, 3, 13).unwrap());
+		// 2025-03-12 is a wednesday
+		let next = Recurrence::Weekly(vec![Weekday::Mon]).next_due(Some(due), completed_at);
+		assert_eq!(date_of(next), NaiveDate::from_ymd_opt(2025, 3, 17).unwrap());
+		let next = Recurrence::Monthly(3).next_due(Some(due), completed_at);
+		assert_eq!(date_of(next), NaiveDate::from_ymd_opt(2025, 4, 3).unwrap());
+		let next = Recurrence::AfterCompletion(2).next_due(Some(due), completed_at);
+		assert_eq!(date_of(next), NaiveDate::from_ymd_opt(2025, 3, 14).unwrap());
+		// the time of day of the due date is kept
+		assert_eq!(next.unwrap().with_timezone(&Local).time(), due.with_timezone(&Local).time());
+	}
+
+	#[test]
+	fn early_completion_continues_from_the_due_date() {
+		let next = Recurrence::Daily.next_due(Some(at(2025, 3, 20)), at(2025, 3, 12));
+		assert_eq!(date_of(next), NaiveDate::from_ymd_opt(2025, 3, 21).unwrap());
+	}
+}
@@ -0,0 +1,288 @@
+use std::{fs, io, path::Path};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+// version of the files this build writes, the archive and the undo history have the todos of the data file
+pub const SCHEMA_VERSION: u64 = 2;
+
+// MIGRATIONS[n] upgrades a version n document to version n + 1, they run one after the other
+const MIGRATIONS: [fn(&mut Value, Kind); SCHEMA_VERSION as usize] = [
+	lists_from_single_list,
+	statuses_from_completed,
+];
+
+// the file a document comes from, each keeps its todos in other places
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+	Workspace,
+	Archive,
+	History,
+}
+
+impl Kind {
+	// data files without a version are from before there were versions, the archive and the history
+	// got theirs later and were written as version 2 until then
+	fn unversioned(self) -> u64 {
+		match self {
+			Kind::Workspace => 0,
+			Kind::Archive | Kind::History => 2,
+		}
+	}
+
+	fn describe(self) -> &'static str {
+		match self {
+			Kind::Workspace => "the data file",
+			Kind::Archive => "the archive",
+			Kind::History => "the undo history",
+		}
+	}
+}
+
+// upgrades the document to the current version in place, returns the version it was at when it was older
+pub fn migrate(document: &mut Value, kind: Kind) -> io::Result<Option<u64>> {
+	let version = match document.get("version") {
+		None => kind.unversioned(),
+		Some(version) => version.as_u64().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "the schema version is not a number"))?,
+	};
+	if version > SCHEMA_VERSION {
+		return Err(io::Error::new(
+			io::ErrorKind::Unsupported,
+			format!("{} has schema version {} but this build only understands up to version {}, update todo_tui to open it", kind.describe(), version, SCHEMA_VERSION),
+		));
+	}
+	for migration in &MIGRATIONS[version as usize..] {
+		migration(document, kind);
+	}
+	set_version(document);
+	Ok((version < SCHEMA_VERSION).then_some(version))
+}
+
+pub fn set_version(document: &mut Value) {
+	if let Some(object) = document.as_object_mut() {
+		object.insert(String::from("version"), json!(SCHEMA_VERSION));
+	}
+}
+
+// the json written for a file, with the version it is at
+pub fn to_versioned_json(value: &impl Serialize) -> io::Result<String> {
+	let mut document = serde_json::to_value(value)?;
+	set_version(&mut document);
+	Ok(serde_json::to_string(&document)?)
+}
+
+// the document of a file migrated to the current version in memory, and the version it was at when it was older
+pub fn read_document(filename: &Path, kind: Kind) -> io::Result<(Value, Option<u64>)> {
+	let contents = fs::read_to_string(filename)?;
+	let mut document: Value = serde_json::from_str(&contents)?;
+	let version = migrate(&mut document, kind)?;
+	Ok((document, version))
+}
+
+// like read_document, but an older file is first copied next to it as <filename>.v<version>.bak.
+// one from a newer version is not read at all
+pub fn read_migrated(filename: &Path, kind: Kind) -> io::Result<Value> {
+	let (document, version) = read_document(filename, kind)?;
+	if let Some(version) = version {
+		let mut backup = filename.as_os_str().to_owned();
+		backup.push(format!(".v{}.bak", version));
+		fs::copy(filename, backup)?;
+	}
+	Ok(document)
+}
+
+// every top level todo of the document, wherever the kind of file keeps them
+fn for_each_todo(document: &mut Value, kind: Kind, f: &mut impl FnMut(&mut Value)) {
+	fn each(array: Option<&mut Value>, f: &mut impl FnMut(&mut Value)) {
+		if let Some(items) = array.and_then(Value::as_array_mut) {
+			items.iter_mut().for_each(f);
+		}
+	}
+	fn in_lists(document: &mut Value, f: &mut impl FnMut(&mut Value)) {
+		each(document.get_mut("lists"), &mut |list| each(list.get_mut("todos"), f));
+	}
+	fn in_entries(document: Option<&mut Value>, f: &mut impl FnMut(&mut Value)) {
+		if let Some(document) = document {
+			each(document.get_mut("entries"), &mut |entry| {
+				if let Some(todo) = entry.get_mut("todo") {
+					f(todo);
+				}
+			});
+		}
+	}
+	match kind {
+		Kind::Workspace => {
+			in_lists(document, f);
+			in_entries(document.get_mut("trash"), f);
+		}
+		Kind::Archive => in_entries(Some(document), f),
+		Kind::History => {
+			for steps in ["undo", "redo"] {
+				each(document.get_mut(steps), &mut |snapshot| {
+					in_lists(snapshot, f);
+					in_entries(snapshot.get_mut("trash"), f);
+					in_entries(snapshot.get_mut("archive"), f);
+				});
+			}
+		}
+	}
+}
+
+// 0 -> 1: files written before there were several lists hold a single list, it becomes the only one
+fn lists_from_single_list(document: &mut Value, kind: Kind) {
+	if kind == Kind::Workspace && document.get("lists").is_none() {
+		let list = document.take();
+		*document = json!({ "lists": [list], "active": 0 });
+	}
+}
+
+// 1 -> 2: todos only knew whether they were completed, a completed todo without a status history is done
+fn statuses_from_completed(document: &mut Value, kind: Kind) {
+	fn upgrade(todo: &mut Value) {
+		let Some(todo) = todo.as_object_mut() else {
+			return;
+		};
+		let has_history = todo.get("status_history").and_then(Value::as_array).is_some_and(|history| !history.is_empty());
+		if todo.remove("completed").and_then(|completed| completed.as_bool()) == Some(true) && !has_history {
+			todo.insert(String::from("status"), json!("Done"));
+		}
+		if let Some(children) = todo.get_mut("children").and_then(Value::as_array_mut) {
+			children.iter_mut().for_each(upgrade);
+		}
+	}
+	for_each_todo(document, kind, &mut upgrade);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::workspace::Workspace;
+
+	#[test]
+	fn flat_file_becomes_the_only_list() {
+		let mut document = json!({
+			"todos": [
+				{ "title": "open", "body": null, "creation_date": 1700000000, "last_edit_date": 1700000000, "completed": false },
+				{ "title": "finished", "body": null, "creation_date": 1700000000, "last_edit_date": 1700000000, "completed": true },
+			],
+		});
+		assert_eq!(migrate(&mut document, Kind::Workspace).unwrap(), Some(0));
+		assert_eq!(document["version"], json!(SCHEMA_VERSION));
+		assert_eq!(document["active"], json!(0));
+		let todos = &document["lists"][0]["todos"];
+		assert_eq!(todos[0]["title"], json!("open"));
+		assert_eq!(todos[0].get("status"), None);
+		assert_eq!(todos[1]["status"], json!("Done"));
+		let workspace: Workspace = serde_json::from_value(document).unwrap();
+		assert_eq!(workspace.lists().len(), 1);
+		assert_eq!(workspace.lists()[0].len(), 2);
+	}
+
+	#[test]
+	fn completed_todos_become_done() {
+		let mut document = json!({
+			"version": 1,
+			"lists": [{
+				"name": "Todo",
+				"todos": [{
+					"title": "parent",
+					"completed": true,
+					"children": [
+						{ "title": "done child", "completed": true },
+						{ "title": "open child", "completed": false },
+					],
+				}],
+			}],
+		});
+		assert_eq!(migrate(&mut document, Kind::Workspace).unwrap(), Some(1));
+		let parent = &document["lists"][0]["todos"][0];
+		assert_eq!(parent["status"], json!("Done"));
+		assert_eq!(parent.get("completed"), None);
+		assert_eq!(parent["children"][0]["status"], json!("Done"));
+		assert_eq!(parent["children"][1].get("status"), None);
+		assert_eq!(parent["children"][1].get("completed"), None);
+	}
+
+	#[test]
+	fn completed_todo_with_a_status_history_keeps_its_status() {
+		let mut document = json!({
+			"version": 1,
+			"lists": [{ "todos": [{ "title": "cancelled", "completed": true, "status": "Cancelled", "status_history": [{ "status": "Cancelled" }] }] }],
+		});
+		migrate(&mut document, Kind::Workspace).unwrap();
+		assert_eq!(document["lists"][0]["todos"][0]["status"], json!("Cancelled"));
+	}
+
+	#[test]
+	fn current_version_is_left_as_is() {
+		let mut document = json!({ "version": SCHEMA_VERSION, "lists": [] });
+		let before = document.clone();
+		assert_eq!(migrate(&mut document, Kind::Workspace).unwrap(), None);
+		assert_eq!(document, before);
+	}
+
+	#[test]
+	fn newer_version_is_unsupported() {
+		let mut document = json!({ "version": SCHEMA_VERSION + 1, "lists": [] });
+		let before = document.clone();
+		let error = migrate(&mut document, Kind::Workspace).unwrap_err();
+		assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+		assert_eq!(document, before);
+	}
+
+	#[test]
+	fn archived_todos_are_migrated() {
+		let mut document = json!({
+			"version": 1,
+			"entries": [{ "list": "Work", "archived_at": 1700000000, "todo": { "title": "report", "completed": true } }],
+		});
+		assert_eq!(migrate(&mut document, Kind::Archive).unwrap(), Some(1));
+		let todo = &document["entries"][0]["todo"];
+		assert_eq!(todo["status"], json!("Done"));
+		assert_eq!(todo.get("completed"), None);
+		// an archive is never turned into a list
+		assert_eq!(document.get("lists"), None);
+	}
+
+	#[test]
+	fn todos_of_undo_steps_are_migrated() {
+		let mut document = json!({
+			"version": 1,
+			"undo": [{
+				"lists": [{ "todos": [{ "title": "listed", "completed": true }] }],
+				"trash": { "entries": [{ "todo": { "title": "trashed", "completed": true } }] },
+				"archive": { "entries": [{ "todo": { "title": "archived", "completed": true } }] },
+			}],
+			"redo": [{ "lists": [{ "todos": [{ "title": "redone", "completed": true }] }] }],
+		});
+		assert_eq!(migrate(&mut document, Kind::History).unwrap(), Some(1));
+		let snapshot = &document["undo"][0];
+		assert_eq!(snapshot["lists"][0]["todos"][0]["status"], json!("Done"));
+		assert_eq!(snapshot["trash"]["entries"][0]["todo"]["status"], json!("Done"));
+		assert_eq!(snapshot["archive"]["entries"][0]["todo"]["status"], json!("Done"));
+		assert_eq!(document["redo"][0]["lists"][0]["todos"][0]["status"], json!("Done"));
+	}
+
+	#[test]
+	fn archive_and_history_without_a_version_are_current() {
+		for kind in [Kind::Archive, Kind::History] {
+			let mut document = json!({ "entries": [{ "todo": { "title": "cancelled", "status": "Cancelled" } }], "undo": [] });
+			assert_eq!(migrate(&mut document, kind).unwrap(), None);
+			assert_eq!(document["version"], json!(SCHEMA_VERSION));
+			assert_eq!(document["entries"][0]["todo"]["status"], json!("Cancelled"));
+		}
+	}
+
+	#[test]
+	fn newer_archive_or_history_is_unsupported() {
+		for kind in [Kind::Archive, Kind::History] {
+			let mut document = json!({ "version": SCHEMA_VERSION + 1, "entries": [], "undo": [] });
+			assert_eq!(migrate(&mut document, kind).unwrap_err().kind(), io::ErrorKind::Unsupported);
+		}
+	}
+
+	#[test]
+	fn version_that_is_not_a_number_is_invalid() {
+		let mut document = json!({ "version": "two", "lists": [] });
+		assert_eq!(migrate(&mut document, Kind::Workspace).unwrap_err().kind(), io::ErrorKind::InvalidData);
+	}
+}
@@ -0,0 +1,38 @@
+use serde::{Serialize, Deserialize};
+
+use crate::pomodoro::PomodoroSettings;
+
+// preferences stored alongside the lists in the data file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Settings {
+	#[serde(default)]
+	pub pomodoro: PomodoroSettings,
+	// closed todos are archived automatically once they have been closed for this many days
+	#[serde(default)]
+	pub archive_after_days: Option<u32>,
+	// deleted todos are purged from the trash on load once they have been there this many days
+	#[serde(default = "default_trash_retention_days")]
+	pub trash_retention_days: u32,
+	// timestamped copies of the data file kept in the backups directory, one is taken on start and every so often while saving
+	#[serde(default = "default_backup_count")]
+	pub backup_count: u32,
+}
+
+fn default_trash_retention_days() -> u32 {
+	30
+}
+
+pub fn default_backup_count() -> u32 {
+	10
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		Settings {
+			pomodoro: PomodoroSettings::default(),
+			archive_after_days: None,
+			trash_retention_days: default_trash_retention_days(),
+			backup_count: default_backup_count(),
+		}
+	}
+}
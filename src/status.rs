@@ -0,0 +1,80 @@
+use chrono::{DateTime, Local, Utc};
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Serialize, Deserialize};
+
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub enum Status {
+	#[default]
+	Todo,
+	InProgress,
+	Blocked,
+	Waiting,
+	Done,
+	Cancelled,
+}
+
+impl Status {
+	// in the order they are cycled through, also the order of the number keys
+	pub const ALL: [Status; 6] = [Status::Todo, Status::InProgress, Status::Blocked, Status::Waiting, Status::Done, Status::Cancelled];
+
+	pub fn next(self) -> Self {
+		let idx = Self::ALL.iter().position(|&status| status == self).unwrap_or(0);
+		Self::ALL[(idx + 1) % Self::ALL.len()]
+	}
+
+	// done and cancelled todos need no more work
+	pub fn is_closed(self) -> bool {
+		matches!(self, Status::Done | Status::Cancelled)
+	}
+
+	pub fn label(self) -> &'static str {
+		match self {
+			Status::Todo => "Todo",
+			Status::InProgress => "In progress",
+			Status::Blocked => "Blocked",
+			Status::Waiting => "Waiting",
+			Status::Done => "Done",
+			Status::Cancelled => "Cancelled",
+		}
+	}
+
+	pub fn icon(self) -> &'static str {
+		match self {
+			Status::Todo => " ",
+			Status::InProgress => "▶",
+			Status::Blocked => "✗",
+			Status::Waiting => "…",
+			Status::Done => "✓",
+			Status::Cancelled => "-",
+		}
+	}
+
+	pub fn style(self) -> Style {
+		match self {
+			Status::Todo => Style::default().fg(Color::Yellow),
+			Status::InProgress => Style::default().fg(Color::Cyan),
+			Status::Blocked => Style::default().fg(Color::Red),
+			Status::Waiting => Style::default().fg(Color::Magenta),
+			Status::Done => Style::default().fg(Color::Green),
+			Status::Cancelled => Style::default().fg(Color::DarkGray).add_modifier(Modifier::CROSSED_OUT),
+		}
+	}
+}
+
+// a todo entering a status, the history of a todo is a list of these
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusChange {
+	pub status: Status,
+	#[serde(with = "chrono::serde::ts_seconds")]
+	at: DateTime<Utc>,
+}
+
+impl StatusChange {
+	pub fn now(status: Status) -> Self {
+		StatusChange { status, at: Utc::now() }
+	}
+
+	pub fn at(&self) -> DateTime<Local> {
+		self.at.with_timezone(&chrono::Local)
+	}
+}
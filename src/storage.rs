@@ -0,0 +1,76 @@
+use std::{fs::{self, File, OpenOptions}, io::{self, Write}, path::Path};
+
+// writes next to the target, syncs and renames over it, so a crash or a full disk
+// leaves either the old file or the new one and never half of one
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+	let file_name = path.file_name().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "the path has no file name"))?;
+	let mut temp_name = file_name.to_owned();
+	temp_name.push(format!(".{}.tmp", std::process::id()));
+	let temp_path = path.with_file_name(temp_name);
+	let written = OpenOptions::new()
+		.write(true)
+		.create(true)
+		.truncate(true)
+		.open(&temp_path)
+		.and_then(|mut file| {
+			file.write_all(contents)?;
+			file.sync_all()
+		})
+		.and_then(|_| fs::rename(&temp_path, path));
+	if written.is_err() {
+		let _ = fs::remove_file(&temp_path);
+		return written;
+	}
+	// the rename only survives a power loss once the directory itself is synced
+	if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+		if let Ok(dir) = File::open(dir) {
+			let _ = dir.sync_all();
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+	use super::*;
+	use std::path::PathBuf;
+
+	// an empty directory for one test under the system temp directory
+	pub fn scratch_dir(name: &str) -> PathBuf {
+		let dir = std::env::temp_dir().join(format!("todo_tui-{}-{}", std::process::id(), name));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).unwrap();
+		dir
+	}
+
+	fn file_names(dir: &Path) -> Vec<String> {
+		let mut names: Vec<String> = fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned()).collect();
+		names.sort();
+		names
+	}
+
+	#[test]
+	fn writes_and_replaces_without_leaving_the_temp_file() {
+		let dir = scratch_dir("write");
+		let path = dir.join("todos.json");
+		write_atomically(&path, b"first").unwrap();
+		assert_eq!(fs::read(&path).unwrap(), b"first");
+		write_atomically(&path, b"second").unwrap();
+		assert_eq!(fs::read(&path).unwrap(), b"second");
+		assert_eq!(file_names(&dir), ["todos.json"]);
+		fs::remove_dir_all(dir).unwrap();
+	}
+
+	#[test]
+	fn failed_write_keeps_what_was_there() {
+		let dir = scratch_dir("failed_write");
+		// a directory can't be renamed over
+		let path = dir.join("todos.json");
+		fs::create_dir(&path).unwrap();
+		assert!(write_atomically(&path, b"new").is_err());
+		assert!(path.is_dir());
+		assert_eq!(file_names(&dir), ["todos.json"]);
+		assert!(write_atomically(&dir.join("missing/todos.json"), b"new").is_err());
+		fs::remove_dir_all(dir).unwrap();
+	}
+}
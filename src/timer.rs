@@ -14,16 +14,32 @@ impl Timer {
 		}
 	}
 
+	// a timer without a duration that already ran for `elapsed`, used to count up instead of down
+	pub fn stopwatch(elapsed: Duration) -> Self {
+		Timer {
+			start_time: Instant::now().checked_sub(elapsed).unwrap_or_else(Instant::now),
+			duration: Duration::ZERO,
+		}
+	}
+
+	pub fn duration(&self) -> Duration {
+		self.duration
+	}
+
+	pub fn elapsed(&self) -> Duration {
+		self.start_time.elapsed()
+	}
+
 	pub fn is_elapsed(&self) -> bool {
 		self.start_time.elapsed() > self.duration
 	}
 
 	pub fn remaining(&self) -> Duration {
 		if self.is_elapsed() {
-			return Duration::from_secs(0)
+			Duration::from_secs(0)
 		}
 		else {
 			self.duration - self.start_time.elapsed()
 		}
 	}
-}
\ No newline at end of file
+}
@@ -1,45 +1,431 @@
-use std::{fs::{File, OpenOptions}, io::{Read, Write}};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use ratatui::widgets::{List, ListItem, Block, Borders};
 use ratatui::text::{Line, Span};
-use ratatui::style::{Style, Color};
+use ratatui::style::{Style, Color, Modifier};
 
 
-use chrono::{DateTime, Local, Utc};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
 use serde::{Serialize, Deserialize};
-use serde_json;
 
-#[derive(Debug ,Serialize, Deserialize)]
+use crate::recurrence::Recurrence;
+use crate::changelog::FieldChange;
+use crate::estimate::Estimate;
+use crate::fields::{FieldDef, FieldFilter, FieldValue};
+use crate::links::Link;
+use crate::notes::Note;
+use crate::status::{Status, StatusChange};
+
+// 0 is never handed out, it marks items loaded from files written before ids existed
+pub type TodoId = u64;
+
+// hands out the todo ids of a whole workspace. an id is never given out twice, a todo keeps it in every list
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct IdCounter(TodoId);
+
+impl Default for IdCounter {
+	fn default() -> Self {
+		IdCounter(1)
+	}
+}
+
+impl IdCounter {
+	pub fn take(&mut self) -> TodoId {
+		let id = self.0.max(1);
+		self.0 = id + 1;
+		id
+	}
+
+	// ids up to this one are in use already
+	pub fn skip_past(&mut self, id: TodoId) {
+		self.0 = self.0.max(id + 1);
+	}
+
+	pub fn catch_up(&mut self, other: IdCounter) {
+		self.0 = self.0.max(other.0);
+	}
+}
+
+// gives a new id to every todo of the tree that has none or one that is taken. returns the new id by the old one
+// for the first todo that had it, later duplicates in the tree are not what other todos of it pointed to
+pub fn renumber_taken(todos: &mut [TodoItem], taken: &mut HashSet<TodoId>, ids: &mut IdCounter) -> HashMap<TodoId, TodoId> {
+	let mut renumbered = HashMap::new();
+	let mut in_tree = HashSet::new();
+	for_each_in_mut(todos, &mut |todo| {
+		let first_in_tree = in_tree.insert(todo.id);
+		if todo.id == 0 || !taken.insert(todo.id) {
+			let new_id = ids.take();
+			if first_in_tree && todo.id != 0 {
+				renumbered.insert(todo.id, new_id);
+			}
+			todo.id = new_id;
+			taken.insert(new_id);
+		}
+	});
+	renumbered
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TodoItem {
+	#[serde(default)]
+	id: TodoId,
 	title: String,
 	body: Option<String>,
 	#[serde(with = "chrono::serde::ts_seconds")]
 	creation_date: DateTime<Utc>,
 	#[serde(with = "chrono::serde::ts_seconds")]
 	last_edit_date: DateTime<Utc>,
-	completed: bool,
+	#[serde(default, with = "chrono::serde::ts_seconds_option")]
+	due_date: Option<DateTime<Utc>>,
+	#[serde(default)]
+	priority: Priority,
+	#[serde(default)]
+	tags: BTreeSet<String>,
+	#[serde(default)]
+	recurrence: Option<Recurrence>,
+	#[serde(default)]
+	blocked_by: BTreeSet<TodoId>,
+	#[serde(default)]
+	time_log: Vec<TimeInterval>,
+	#[serde(default)]
+	pomodoros: u32,
+	#[serde(default)]
+	estimate: Option<Estimate>,
+	#[serde(default)]
+	status: Status,
+	// every status the todo went through with when, the last one is the current status
+	#[serde(default)]
+	status_history: Vec<StatusChange>,
+	// when the todo was last done or cancelled, None while it is open
+	#[serde(default, with = "chrono::serde::ts_seconds_option")]
+	completed_at: Option<DateTime<Utc>>,
+	#[serde(default)]
+	change_log: Vec<FieldChange>,
+	// oldest first
+	#[serde(default)]
+	notes: Vec<Note>,
+	// values of the custom fields of the list, by field name
+	#[serde(default)]
+	fields: BTreeMap<String, FieldValue>,
+	// urls and file references found in the title and body, kept up to date on every edit
+	#[serde(default)]
+	links: Vec<Link>,
+	#[serde(default)]
+	children: Vec<TodoItem>,
+	#[serde(default)]
+	collapsed: bool,
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub enum Priority {
+	#[default]
+	None,
+	Low,
+	Medium,
+	High,
+	Urgent,
+}
+
+impl Priority {
+	pub fn raised(self) -> Self {
+		match self {
+			Priority::None => Priority::Low,
+			Priority::Low => Priority::Medium,
+			Priority::Medium => Priority::High,
+			Priority::High | Priority::Urgent => Priority::Urgent,
+		}
+	}
+
+	pub fn lowered(self) -> Self {
+		match self {
+			Priority::None | Priority::Low => Priority::None,
+			Priority::Medium => Priority::Low,
+			Priority::High => Priority::Medium,
+			Priority::Urgent => Priority::High,
+		}
+	}
+
+	pub fn label(self) -> &'static str {
+		match self {
+			Priority::None => "none",
+			Priority::Low => "low",
+			Priority::Medium => "medium",
+			Priority::High => "high",
+			Priority::Urgent => "urgent",
+		}
+	}
+
+	pub fn marker(self) -> Span<'static> {
+		match self {
+			Priority::None => Span::raw(""),
+			Priority::Low => Span::styled("! ", Style::default().fg(Color::Blue)),
+			Priority::Medium => Span::styled("!! ", Style::default().fg(Color::Green)),
+			Priority::High => Span::styled("!!! ", Style::default().fg(Color::LightRed)),
+			Priority::Urgent => Span::styled("!!!! ", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+		}
+	}
+}
+
+// time tracked and pomodoros done on each todo, by id. they are not undo steps,
+// undoing carries them over from the state it replaces
+#[derive(Debug, Default)]
+pub struct Effort(HashMap<TodoId, (Vec<TimeInterval>, u32)>);
+
+impl Effort {
+	pub fn collect<'a>(&mut self, todos: impl IntoIterator<Item = &'a TodoItem>) {
+		for todo in todos {
+			self.0.insert(todo.id, (todo.time_log.clone(), todo.pomodoros));
+			self.collect(&todo.children);
+		}
+	}
+
+	pub fn carry_over(&self, todos: &mut [TodoItem]) {
+		for_each_in_mut(todos, &mut |todo| {
+			if let Some((time_log, pomodoros)) = self.0.get(&todo.id) {
+				todo.time_log = time_log.clone();
+				todo.pomodoros = *pomodoros;
+			}
+		});
+	}
+}
+
+// one tracked stretch of work, end is None while the clock is running
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeInterval {
+	#[serde(with = "chrono::serde::ts_seconds")]
+	start: DateTime<Utc>,
+	#[serde(default, with = "chrono::serde::ts_seconds_option")]
+	end: Option<DateTime<Utc>>,
+}
+
+impl TimeInterval {
+	pub fn start(&self) -> DateTime<Local> {
+		self.start.with_timezone(&chrono::Local)
+	}
+	pub fn end(&self) -> Option<DateTime<Local>> {
+		self.end.map(|end| end.with_timezone(&chrono::Local))
+	}
+	pub fn duration(&self) -> Duration {
+		(self.end.unwrap_or_else(Utc::now) - self.start).max(Duration::zero())
+	}
+}
+
+// "1h 05m" for long stretches, "12m 30s" below an hour
+pub fn format_duration(duration: Duration) -> String {
+	let seconds = duration.num_seconds().max(0);
+	if seconds >= 3600 {
+		format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60)
+	} else {
+		format!("{}m {:02}s", seconds / 60, seconds % 60)
+	}
+}
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum DueState {
+	NoDueDate,
+	Upcoming,
+	DueToday,
+	Overdue,
+}
+
+#[derive(PartialEq, Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub enum SortOrder {
+	#[default]
+	CreationDate,
+	DueDate,
+	Priority,
+	// index of a custom field of the list
+	Field(usize),
+}
+
+impl SortOrder {
+	// the custom fields of the list come after the built in orders
+	pub fn next(self, fields: &[FieldDef]) -> Self {
+		match self {
+			SortOrder::CreationDate => SortOrder::DueDate,
+			SortOrder::DueDate => SortOrder::Priority,
+			SortOrder::Priority if !fields.is_empty() => SortOrder::Field(0),
+			SortOrder::Field(idx) if idx + 1 < fields.len() => SortOrder::Field(idx + 1),
+			SortOrder::Priority | SortOrder::Field(_) => SortOrder::CreationDate,
+		}
+	}
+
+	pub fn label(self, fields: &[FieldDef]) -> String {
+		match self {
+			SortOrder::CreationDate => String::from("creation date"),
+			SortOrder::DueDate => String::from("due date"),
+			SortOrder::Priority => String::from("priority"),
+			SortOrder::Field(idx) => fields.get(idx).map_or_else(|| String::from("creation date"), |field| field.name.clone()),
+		}
+	}
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Filter {
+	All,
+	DueSoon,
+	Overdue,
+	Tags(Vec<String>),
+	// uncompleted todos that are not waiting on another one
+	Ready,
+	Field(FieldFilter),
+}
+
+impl Filter {
+	pub fn next(&self) -> Self {
+		match self {
+			Filter::All => Filter::DueSoon,
+			Filter::DueSoon => Filter::Overdue,
+			Filter::Overdue => Filter::Ready,
+			Filter::Ready | Filter::Tags(_) | Filter::Field(_) => Filter::All,
+		}
+	}
+
+	pub fn label(&self) -> String {
+		match self {
+			Filter::All => String::from("all"),
+			Filter::DueSoon => String::from("due today or overdue"),
+			Filter::Overdue => String::from("overdue"),
+			Filter::Tags(tags) => format!("tags: {}", tags.join(", ")),
+			Filter::Ready => String::from("ready"),
+			Filter::Field(filter) => filter.describe(),
+		}
+	}
+
+	pub fn matches(&self, todo: &TodoItem, list: &TodoList) -> bool {
+		match self {
+			Filter::All => true,
+			Filter::DueSoon => matches!(todo.due_state(), DueState::DueToday | DueState::Overdue),
+			Filter::Overdue => todo.due_state() == DueState::Overdue,
+			Filter::Tags(tags) => tags.iter().any(|tag| todo.tags.contains(tag)),
+			Filter::Ready => !todo.completed() && todo.status != Status::Blocked && !list.is_blocked(todo),
+			Filter::Field(filter) => filter.matches(todo, &list.fields),
+		}
+	}
+}
+
+// tags are lowercased and may be written with or without a leading '#'
+pub fn parse_tags(input: &str) -> BTreeSet<String> {
+	input
+		.split(|c: char| c.is_whitespace() || c == ',')
+		.map(|tag| tag.trim_start_matches('#').to_lowercase())
+		.filter(|tag| !tag.is_empty())
+		.collect()
+}
+
+// the same tag always gets the same colour so chips are recognisable across the list
+pub fn tag_chip(tag: &str) -> Span<'_> {
+	const PALETTE: [Color; 6] = [Color::Cyan, Color::Magenta, Color::Green, Color::Blue, Color::LightRed, Color::LightYellow];
+	let hash = tag.bytes().fold(0usize, |acc, byte| acc.wrapping_mul(31).wrapping_add(byte as usize));
+	Span::styled(
+		format!(" {} ", tag),
+		Style::default().fg(Color::Black).bg(PALETTE[hash % PALETTE.len()]),
+	)
+}
+
+// accepts "today", "tomorrow", "+3d", "dd-mm-YYYY" or "dd-mm-YYYY HH:MM", in local time.
+// a date without a time is due at the end of that day
+pub fn parse_due_date(input: &str) -> Option<DateTime<Utc>> {
+	let input = input.trim().to_lowercase();
+	let end_of_day = NaiveTime::from_hms_opt(23, 59, 59)?;
+	let today = Local::now().date_naive();
+	let naive = if input == "today" {
+		today.and_time(end_of_day)
+	} else if input == "tomorrow" {
+		(today + Duration::days(1)).and_time(end_of_day)
+	} else if let Some(days) = input.strip_prefix('+').and_then(|rest| rest.strip_suffix('d')) {
+		(today + Duration::days(days.parse().ok()?)).and_time(end_of_day)
+	} else if let Ok(date_time) = NaiveDateTime::parse_from_str(&input, "%d-%m-%Y %H:%M") {
+		date_time
+	} else {
+		NaiveDate::parse_from_str(&input, "%d-%m-%Y").ok()?.and_time(end_of_day)
+	};
+	Local.from_local_datetime(&naive).earliest().map(|date| date.with_timezone(&Utc))
+}
+
+// links of the title first, then those of the body that were not in the title
+fn todo_links(title: &str, body: Option<&str>) -> Vec<Link> {
+	let mut links = Link::detect(title);
+	for link in Link::detect(body.unwrap_or_default()) {
+		if !links.contains(&link) {
+			links.push(link);
+		}
+	}
+	links
 }
 
 impl TodoItem {
-	pub fn new(title: String, body: Option<String>) -> Self {
+	pub fn new(id: TodoId, title: String, body: Option<String>) -> Self {
 		let now: DateTime<Utc> = Utc::now();
+		let links = todo_links(&title, body.as_deref());
 		TodoItem {
+			id,
 			title,
 			body,
 			creation_date: now,
 			last_edit_date: now,
-			completed: false,
+			due_date: None,
+			priority: Priority::None,
+			tags: BTreeSet::new(),
+			recurrence: None,
+			blocked_by: BTreeSet::new(),
+			time_log: Vec::new(),
+			pomodoros: 0,
+			estimate: None,
+			status: Status::Todo,
+			status_history: vec![StatusChange::now(Status::Todo)],
+			completed_at: None,
+			change_log: Vec::new(),
+			notes: Vec::new(),
+			fields: BTreeMap::new(),
+			links,
+			children: Vec::new(),
+			collapsed: false,
 		}
 	}
 
 	pub fn complete(&mut self) {
-		if !self.completed {
-			self.completed = true;
-		} else {
-			self.completed = false;
+		let status = if self.status == Status::Done { Status::Todo } else { Status::Done };
+		self.set_status(status);
+	}
+
+	pub fn set_status(&mut self, status: Status) {
+		if self.status != status {
+			if !status.is_closed() {
+				self.completed_at = None;
+			} else if !self.status.is_closed() {
+				// the clock does not keep running on a closed todo
+				self.stop_tracking();
+				self.completed_at = Some(Utc::now());
+			}
+			self.status = status;
+			self.status_history.push(StatusChange::now(status));
 		}
 	}
 
+	// the fields whose edits go in the change log, as they are shown. custom fields only when set
+	fn logged_fields(&self) -> BTreeMap<String, String> {
+		let mut fields = BTreeMap::from([
+			(String::from("title"), self.title.clone()),
+			(String::from("body"), self.body.clone().unwrap_or_default()),
+			(String::from("due date"), self.due_date().map(|due| due.format("%d-%m-%Y %H:%M").to_string()).unwrap_or_default()),
+			(String::from("priority"), self.priority.label().to_string()),
+			(String::from("tags"), self.tags.iter().cloned().collect::<Vec<_>>().join(" ")),
+			(String::from("repeat"), self.recurrence.as_ref().map(|recurrence| recurrence.describe()).unwrap_or_default()),
+			(String::from("estimate"), self.estimate.map(Estimate::describe).unwrap_or_default()),
+			(String::from("blocked by"), self.blocked_by.iter().map(|id| format!("#{}", id)).collect::<Vec<_>>().join(" ")),
+		]);
+		fields.extend(self.fields.iter().map(|(name, value)| (name.clone(), value.display())));
+		fields
+	}
+
+	fn refresh_links(&mut self) {
+		self.links = todo_links(&self.title, self.body.as_deref());
+	}
+
 	// getters so as to not make items public
+	pub fn id(&self) -> TodoId {
+		self.id
+	}
 	pub fn title(&self) -> &str {
 		&self.title
 	}
@@ -52,140 +438,936 @@ impl TodoItem {
 	pub fn last_edit_date(&self) -> DateTime<Local> {
 		self.last_edit_date.with_timezone(&chrono::Local)
 	}
+	pub fn due_date(&self) -> Option<DateTime<Local>> {
+		self.due_date.map(|date| date.with_timezone(&chrono::Local))
+	}
+	pub fn priority(&self) -> Priority {
+		self.priority
+	}
+	pub fn tags(&self) -> impl Iterator<Item = &String> {
+		self.tags.iter()
+	}
+	pub fn recurrence(&self) -> Option<&Recurrence> {
+		self.recurrence.as_ref()
+	}
+	pub fn blocked_by(&self) -> impl Iterator<Item = &TodoId> {
+		self.blocked_by.iter()
+	}
+	pub fn time_log(&self) -> &[TimeInterval] {
+		&self.time_log
+	}
+	pub fn pomodoros(&self) -> u32 {
+		self.pomodoros
+	}
+	pub fn estimate(&self) -> Option<Estimate> {
+		self.estimate
+	}
+
+	pub fn tracked_time(&self) -> Duration {
+		self.time_log.iter().map(|interval| interval.duration()).fold(Duration::zero(), |total, duration| total + duration)
+	}
+
+	pub fn running_interval(&self) -> Option<&TimeInterval> {
+		self.time_log.iter().find(|interval| interval.end.is_none())
+	}
+
+	fn stop_tracking(&mut self) -> bool {
+		let now = Utc::now();
+		let mut stopped = false;
+		for interval in self.time_log.iter_mut().filter(|interval| interval.end.is_none()) {
+			interval.end = Some(now);
+			stopped = true;
+		}
+		stopped
+	}
+	pub fn status(&self) -> Status {
+		self.status
+	}
+	pub fn status_history(&self) -> &[StatusChange] {
+		&self.status_history
+	}
+	// done or cancelled
 	pub fn completed(&self) -> bool {
-		self.completed
+		self.status.is_closed()
+	}
+	// when the todo was done or cancelled, todos closed before statuses were recorded use their last edit
+	pub fn closed_at(&self) -> Option<DateTime<Local>> {
+		if !self.completed() {
+			return None;
+		}
+		match self.completed_at {
+			Some(completed_at) => Some(completed_at.with_timezone(&chrono::Local)),
+			None => Some(self.status_history.last().map_or_else(|| self.last_edit_date(), |change| change.at())),
+		}
+	}
+	pub fn change_log(&self) -> &[FieldChange] {
+		&self.change_log
+	}
+	pub fn notes(&self) -> &[Note] {
+		&self.notes
+	}
+	pub fn field(&self, name: &str) -> Option<&FieldValue> {
+		self.fields.get(name)
+	}
+	pub fn links(&self) -> &[Link] {
+		&self.links
+	}
+	// case insensitive search through the title, body, notes and tags of the todo and its subtasks, the query is lowercase
+	pub fn contains_text(&self, query: &str) -> bool {
+		self.title.to_lowercase().contains(query)
+			|| self.body.as_ref().is_some_and(|body| body.to_lowercase().contains(query))
+			|| self.notes.iter().any(|note| note.text().to_lowercase().contains(query))
+			|| self.tags.iter().any(|tag| tag.contains(query))
+			|| self.children.iter().any(|child| child.contains_text(query))
+	}
+	pub fn children(&self) -> &[TodoItem] {
+		&self.children
+	}
+	pub fn collapsed(&self) -> bool {
+		self.collapsed
+	}
+
+	// completed direct subtasks out of all of them, None for a todo without subtasks
+	pub fn progress(&self) -> Option<(usize, usize)> {
+		if self.children.is_empty() {
+			return None;
+		}
+		let done = self.children.iter().filter(|child| child.completed()).count();
+		Some((done, self.children.len()))
+	}
+
+	fn matches_in_tree(&self, filter: &Filter, list: &TodoList) -> bool {
+		filter.matches(self, list) || self.children.iter().any(|child| child.matches_in_tree(filter, list))
+	}
+
+	pub fn due_state(&self) -> DueState {
+		let Some(due) = self.due_date() else {
+			return DueState::NoDueDate;
+		};
+		if self.completed() {
+			return DueState::Upcoming;
+		}
+		let now = Local::now();
+		if due < now {
+			DueState::Overdue
+		} else if due.date_naive() == now.date_naive() {
+			DueState::DueToday
+		} else {
+			DueState::Upcoming
+		}
+	}
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DependencyError {
+	UnknownTodo(TodoId),
+	SelfReference,
+	// the todo is already, directly or not, a blocker of the given one
+	Cycle(TodoId),
+}
+
+impl std::fmt::Display for DependencyError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			DependencyError::UnknownTodo(id) => write!(f, "there is no todo #{} in this list", id),
+			DependencyError::SelfReference => write!(f, "a todo can't be blocked by itself"),
+			DependencyError::Cycle(id) => write!(f, "#{} already waits on this todo, that would be a cycle", id),
+		}
 	}
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+// "#3 #5" or "3, 5"
+pub fn parse_todo_ids(input: &str) -> Option<BTreeSet<TodoId>> {
+	input
+		.split(|c: char| c.is_whitespace() || c == ',')
+		.filter(|id| !id.is_empty())
+		.map(|id| id.trim_start_matches('#').parse().ok())
+		.collect()
+}
+
+// one line of the tree as it is shown in the list, depth 0 being a top level todo
+#[derive(Debug, Clone, Copy)]
+pub struct TodoRow {
+	pub id: TodoId,
+	pub depth: usize,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct TodoList {
-	todos: Vec<TodoItem>
+	#[serde(default = "default_list_name")]
+	name: String,
+	todos: Vec<TodoItem>,
+	// what the list looked like when it was last shown, restored when switching back to it
+	#[serde(default)]
+	sort_order: SortOrder,
+	#[serde(default)]
+	selected: Option<TodoId>,
+	// custom fields the todos of this list can have
+	#[serde(default)]
+	fields: Vec<FieldDef>,
+}
+
+fn default_list_name() -> String {
+	String::from("Todo")
+}
+
+fn find_in(items: &[TodoItem], id: TodoId) -> Option<&TodoItem> {
+	items.iter().find_map(|todo| {
+		if todo.id == id { Some(todo) } else { find_in(&todo.children, id) }
+	})
+}
+
+fn find_in_mut(items: &mut [TodoItem], id: TodoId) -> Option<&mut TodoItem> {
+	items.iter_mut().find_map(|todo| {
+		if todo.id == id { Some(todo) } else { find_in_mut(&mut todo.children, id) }
+	})
+}
+
+fn remove_from(items: &mut Vec<TodoItem>, id: TodoId) -> Option<TodoItem> {
+	if let Some(idx) = items.iter().position(|todo| todo.id == id) {
+		return Some(items.remove(idx));
+	}
+	items.iter_mut().find_map(|todo| remove_from(&mut todo.children, id))
+}
+
+// the next occurrence is a fresh copy of the closed one, with its subtasks and new ids
+fn next_occurrence(ids: &mut IdCounter, mut next: TodoItem, recurrence: Recurrence) -> TodoItem {
+	let now = Utc::now();
+	next.due_date = recurrence.next_due(next.due_date, now).or(next.due_date);
+	next.recurrence = Some(recurrence);
+	next.creation_date = now;
+	next.last_edit_date = now;
+	for_each_in_mut(std::slice::from_mut(&mut next), &mut |todo| {
+		todo.id = ids.take();
+		todo.status = Status::Todo;
+		todo.status_history = vec![StatusChange::now(Status::Todo)];
+		todo.completed_at = None;
+		todo.change_log.clear();
+		todo.notes.clear();
+		// time and pomodoros stay with the occurrence they were spent on
+		todo.time_log.clear();
+		todo.pomodoros = 0;
+	});
+	next
+}
+
+fn walk_in_ids(items: &[TodoItem]) -> HashSet<TodoId> {
+	let mut out = Vec::new();
+	walk_in(items, &mut out);
+	out.into_iter().map(|todo| todo.id).collect()
+}
+
+fn walk_in<'a>(items: &'a [TodoItem], out: &mut Vec<&'a TodoItem>) {
+	for todo in items {
+		out.push(todo);
+		walk_in(&todo.children, out);
+	}
+}
+
+fn for_each_in_mut(items: &mut [TodoItem], f: &mut impl FnMut(&mut TodoItem)) {
+	for todo in items {
+		f(todo);
+		for_each_in_mut(&mut todo.children, f);
+	}
+}
+
+// field is the name of the custom field sorted by
+fn sort_level(items: &mut [TodoItem], order: SortOrder, field: Option<&str>) {
+	match (order, field) {
+		// todos without a value go last, ties are broken by the newest item
+		(SortOrder::Field(_), Some(field)) => items.sort_by(|a, b| match (a.fields.get(field), b.fields.get(field)) {
+			(Some(a_value), Some(b_value)) => a_value.compare(b_value).then_with(|| b.creation_date.cmp(&a.creation_date)),
+			(a_value, b_value) => b_value.is_some().cmp(&a_value.is_some()).then_with(|| b.creation_date.cmp(&a.creation_date)),
+		}),
+		(SortOrder::CreationDate | SortOrder::Field(_), _) => items.sort_by_key(|todo| std::cmp::Reverse(todo.creation_date)),
+		// soonest first, items without a due date go last
+		(SortOrder::DueDate, _) => items.sort_by_key(|todo| (todo.due_date.is_none(), todo.due_date)),
+		// most important first, ties broken by the soonest due date then by the newest item
+		(SortOrder::Priority, _) => items.sort_by(|a, b| {
+			b.priority.cmp(&a.priority)
+				.then_with(|| (a.due_date.is_none(), a.due_date).cmp(&(b.due_date.is_none(), b.due_date)))
+				.then_with(|| b.creation_date.cmp(&a.creation_date))
+		}),
+	}
+	for todo in items {
+		sort_level(&mut todo.children, order, field);
+	}
+}
+
+// a todo is shown when it matches the filter or when one of its descendants does,
+// so a matching subtask is never hidden without its parents
+fn collect_rows(list: &TodoList, items: &[TodoItem], filter: &Filter, depth: usize, rows: &mut Vec<TodoRow>) {
+	for todo in items.iter().filter(|todo| todo.matches_in_tree(filter, list)) {
+		rows.push(TodoRow { id: todo.id, depth });
+		if !todo.collapsed {
+			collect_rows(list, &todo.children, filter, depth + 1, rows);
+		}
+	}
 }
 
 impl TodoList {
 	pub fn new() -> Self {
+		Self::with_name(default_list_name())
+	}
+
+	pub fn with_name(name: String) -> Self {
 		TodoList {
+			name,
 			todos: Vec::new(),
+			sort_order: SortOrder::default(),
+			selected: None,
+			fields: Vec::new(),
+		}
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn set_name(&mut self, name: String) {
+		self.name = name;
+	}
+
+	pub fn sort_order(&self) -> SortOrder {
+		self.sort_order
+	}
+
+	pub fn selected(&self) -> Option<TodoId> {
+		self.selected
+	}
+
+	pub fn set_view(&mut self, sort_order: SortOrder, selected: Option<TodoId>) {
+		self.sort_order = sort_order;
+		self.selected = selected;
+	}
+
+	// links are found again on load, files written before they were detected have none
+	pub fn refresh_links(&mut self) {
+		for_each_in_mut(&mut self.todos, &mut |todo| todo.refresh_links());
+	}
+
+	// gives an id to every item that has none (old files) or shares one with an item seen before,
+	// the blockers and selection of the list follow. ids has to be past every id already in use
+	pub fn backfill_ids(&mut self, taken: &mut HashSet<TodoId>, ids: &mut IdCounter) {
+		let renumbered = renumber_taken(&mut self.todos, taken, ids);
+		if renumbered.is_empty() {
+			return;
 		}
+		for_each_in_mut(&mut self.todos, &mut |todo| {
+			todo.blocked_by = todo.blocked_by.iter().map(|blocker| *renumbered.get(blocker).unwrap_or(blocker)).collect();
+		});
+		self.selected = self.selected.map(|selected| *renumbered.get(&selected).unwrap_or(&selected));
+	}
+
+	pub fn max_id(&self) -> TodoId {
+		Self::max_id_in(&self.todos)
 	}
 
-	pub fn add_todo(&mut self, title: String, body: Option<String>) {
-		let new_todo = TodoItem::new(title, body);
+	pub fn max_id_in(todos: &[TodoItem]) -> TodoId {
+		walk_in_ids(todos).into_iter().max().unwrap_or(0)
+	}
+
+	// every todo of the tree, parents before their children
+	pub fn walk(&self) -> Vec<&TodoItem> {
+		let mut out = Vec::new();
+		walk_in(&self.todos, &mut out);
+		out
+	}
+
+	pub fn add_todo(&mut self, ids: &mut IdCounter, title: String, body: Option<String>) -> TodoId {
+		let id = ids.take();
+		let new_todo = TodoItem::new(id, title, body);
 		self.todos.insert(0, new_todo);
+		id
+	}
+
+	// puts a todo taken from another list on top. blockers can only be in the same list,
+	// so the ones outside its subtree are dropped
+	pub fn insert_todo(&mut self, ids: &mut IdCounter, todo: TodoItem) -> TodoId {
+		self.insert_subtree(ids, todo, false)
+	}
+
+	// puts a todo back on top of the list it was taken from, its blockers are still there
+	pub fn restore_todo(&mut self, ids: &mut IdCounter, todo: TodoItem) -> TodoId {
+		self.insert_subtree(ids, todo, true)
+	}
+
+	// the subtree keeps its ids. only a todo from a file that numbered each list on its own can find
+	// its id taken, it gets a new one and the blockers inside the subtree follow
+	fn insert_subtree(&mut self, ids: &mut IdCounter, mut todo: TodoItem, keep_outside_blockers: bool) -> TodoId {
+		let mut taken: HashSet<TodoId> = self.walk().iter().map(|todo| todo.id).collect();
+		let subtree: HashSet<TodoId> = walk_in_ids(std::slice::from_ref(&todo));
+		let renumbered = renumber_taken(std::slice::from_mut(&mut todo), &mut taken, ids);
+		for_each_in_mut(std::slice::from_mut(&mut todo), &mut |todo| {
+			todo.blocked_by = todo.blocked_by.iter()
+				.filter_map(|blocker| match renumbered.get(blocker) {
+					Some(&new_id) => Some(new_id),
+					None => (keep_outside_blockers || subtree.contains(blocker)).then_some(*blocker),
+				})
+				.collect();
+		});
+		let id = todo.id;
+		self.todos.insert(0, todo);
+		id
 	}
 
-	pub fn remove_todo(&mut self, idx: usize) -> Option<TodoItem> {
-		if idx < self.todos.len() {
-			Some(self.todos.remove(idx))
+	// subtasks are appended so they keep the order in which a task was broken down
+	pub fn add_subtask(&mut self, ids: &mut IdCounter, parent: TodoId, title: String) -> Option<TodoId> {
+		let parent = self.get_todo_mut(parent)?;
+		let id = ids.take();
+		parent.children.push(TodoItem::new(id, title, None));
+		parent.collapsed = false;
+		Some(id)
+	}
+
+	pub fn remove_todo(&mut self, id: TodoId) -> Option<TodoItem> {
+		remove_from(&mut self.todos, id)
+	}
+
+	// only done or cancelled todos are archived, they take their subtasks with them
+	pub fn archive_todo(&mut self, id: TodoId) -> Option<TodoItem> {
+		if !self.get_todo(id)?.completed() {
+			return None;
 		}
-		else {
-			None
+		self.remove_todo(id)
+	}
+
+	// takes out every todo that was closed before cutoff, parents first
+	pub fn take_closed_before(&mut self, cutoff: DateTime<Local>) -> Vec<TodoItem> {
+		let old: Vec<TodoId> = self.walk()
+			.into_iter()
+			.filter(|todo| todo.closed_at().is_some_and(|closed_at| closed_at < cutoff))
+			.map(|todo| todo.id)
+			.collect();
+		old.into_iter().filter_map(|id| self.remove_todo(id)).collect()
+	}
+
+	// toggles between done and todo
+	pub fn complete_todo(&mut self, ids: &mut IdCounter, id: TodoId, cascade: bool) -> bool {
+		match self.get_todo(id).map(|todo| todo.status) {
+			Some(Status::Done) => self.set_status(ids, id, Status::Todo, cascade),
+			Some(_) => self.set_status(ids, id, Status::Done, cascade),
+			None => false,
 		}
 	}
 
-	pub fn complete_todo(&mut self, idx: usize) -> bool {
-		if let Some(todo) = self.todos.get_mut(idx) {
-			todo.complete();
-			true
+	// with cascade the subtasks all take the new status of the parent.
+	// closing a recurring todo keeps it as the record of that occurrence and schedules the next one
+	pub fn set_status(&mut self, ids: &mut IdCounter, id: TodoId, status: Status, cascade: bool) -> bool {
+		let closed_occurrence = if let Some(todo) = self.get_todo_mut(id) {
+			let was_closed = todo.completed();
+			todo.set_status(status);
+			if cascade {
+				for_each_in_mut(&mut todo.children, &mut |child| child.set_status(status));
+			}
+			if !was_closed && todo.completed() { todo.recurrence.take().map(|recurrence| (todo.clone(), recurrence)) } else { None }
 		}
 		else {
-			false
+			return false;
+		};
+		if let Some((occurrence, recurrence)) = closed_occurrence {
+			let next = next_occurrence(ids, occurrence, recurrence);
+			let siblings = match self.parent_of(id) {
+				Some(parent) => &mut self.get_todo_mut(parent).expect("parent_of returns existing todos").children,
+				None => &mut self.todos,
+			};
+			let idx = siblings.iter().position(|todo| todo.id == id).map_or(0, |idx| idx + 1);
+			siblings.insert(idx, next);
 		}
+		true
+	}
+
+	pub fn get_todo(&self, id: TodoId) -> Option<&TodoItem> {
+		find_in(&self.todos, id)
 	}
 
-	pub fn get_todo(&self, idx: usize) -> Option<&TodoItem> {
-		self.todos.get(idx)
+	fn get_todo_mut(&mut self, id: TodoId) -> Option<&mut TodoItem> {
+		find_in_mut(&mut self.todos, id)
+	}
+
+	// applies an edit to one todo and bumps its last edit date
+	// every field the edit changed is added to the change log of the todo
+	fn edit_todo(&mut self, id: TodoId, edit: impl FnOnce(&mut TodoItem)) -> bool {
+		if let Some(todo) = self.get_todo_mut(id) {
+			let mut before = todo.logged_fields();
+			edit(todo);
+			for (field, new) in todo.logged_fields() {
+				let old = before.remove(&field).unwrap_or_default();
+				if old != new {
+					todo.change_log.push(FieldChange::now(&field, old, new));
+				}
+			}
+			// custom fields that were cleared
+			for (field, old) in before {
+				todo.change_log.push(FieldChange::now(&field, old, String::new()));
+			}
+			todo.refresh_links();
+			todo.last_edit_date = Utc::now();
+			true
+		} else {
+			false
+		}
 	}
 
 	pub fn get_todos(&self) -> &[TodoItem] {
 		&self.todos
 	}
 
+	pub fn into_todos(self) -> Vec<TodoItem> {
+		self.todos
+	}
+
+	pub fn carry_effort(&mut self, effort: &Effort) {
+		effort.carry_over(&mut self.todos);
+	}
+
+	pub fn visible_rows(&self, filter: &Filter) -> Vec<TodoRow> {
+		let mut rows = Vec::new();
+		collect_rows(self, &self.todos, filter, 0, &mut rows);
+		rows
+	}
+
+	pub fn visible_ids(&self, filter: &Filter) -> Vec<TodoId> {
+		self.visible_rows(filter).into_iter().map(|row| row.id).collect()
+	}
+
+	pub fn parent_of(&self, id: TodoId) -> Option<TodoId> {
+		self.walk()
+			.into_iter()
+			.find(|todo| todo.children.iter().any(|child| child.id == id))
+			.map(|todo| todo.id)
+	}
+
+	// returns false when there was nothing to expand or collapse
+	pub fn set_collapsed(&mut self, id: TodoId, collapsed: bool) -> bool {
+		match self.get_todo_mut(id) {
+			Some(todo) if !todo.children.is_empty() && todo.collapsed != collapsed => {
+				todo.collapsed = collapsed;
+				true
+			}
+			_ => false,
+		}
+	}
+
 	pub fn len(&self) -> usize {
-		self.todos.len()
+		self.walk().len()
 	}
 
 	pub fn is_empty(&self) -> bool {
 		self.todos.is_empty()
 	}
 
-	pub fn save_to_file(&self, filename: &str) -> std::io::Result<()> {
-		let json = serde_json::to_string(self)?;
-		let mut file = OpenOptions::new()
-			.write(true)
-			.create(true)
-			.truncate(true)
-			.open(filename)?;
-		file.write_all(json.as_bytes())?;
-		Ok(())
+	// sorts every level of the tree, subtasks stay under their parent
+	pub fn sort(&mut self, order: SortOrder) {
+		let field = match order {
+			SortOrder::Field(idx) => self.fields.get(idx).map(|field| field.name.clone()),
+			_ => None,
+		};
+		sort_level(&mut self.todos, order, field.as_deref());
+	}
+
+	pub fn fields(&self) -> &[FieldDef] {
+		&self.fields
+	}
+
+	pub fn field_def(&self, name: &str) -> Option<&FieldDef> {
+		self.fields.iter().find(|field| field.name == name)
+	}
+
+	// values of fields that are no longer defined stay on the todos, they come back with the field
+	pub fn set_fields(&mut self, fields: Vec<FieldDef>) {
+		self.fields = fields;
+		if matches!(self.sort_order, SortOrder::Field(idx) if idx >= self.fields.len()) {
+			self.sort_order = SortOrder::CreationDate;
+		}
 	}
 
-	pub fn load_from_file(filename: &str) -> std::io::Result<Self> {
-		let mut file = File::open(filename)?;
-		let mut contents = String::new();
-		file.read_to_string(&mut contents)?;
-		let todolist = serde_json::from_str(&contents)?;
-		Ok(todolist)
+	pub fn set_field(&mut self, id: TodoId, name: &str, value: Option<FieldValue>) -> bool {
+		self.edit_todo(id, |todo| match value {
+			Some(value) => {
+				todo.fields.insert(name.to_string(), value);
+			}
+			None => {
+				todo.fields.remove(name);
+			}
+		})
 	}
 
 	pub fn sort_by_date(&mut self) {
-		self.todos.sort_by(|a, b|b.creation_date().cmp(&a.creation_date()))
+		self.sort(SortOrder::CreationDate)
+	}
+
+	pub fn sort_by_due_date(&mut self) {
+		self.sort(SortOrder::DueDate)
+	}
+
+	pub fn sort_by_priority(&mut self) {
+		self.sort(SortOrder::Priority)
+	}
+
+	pub fn filter_due(&self) -> Vec<&TodoItem> {
+		self.walk().into_iter().filter(|&todo|Filter::DueSoon.matches(todo, self)).collect()
 	}
 
 	pub fn filter_completed (&self) -> Vec<&TodoItem> {
-		self.todos.iter().filter(|&todo|todo.completed()).collect()
+		self.walk().into_iter().filter(|&todo|todo.completed()).collect()
 	}
 
 	pub fn filter_uncompleted(&self) -> Vec<&TodoItem> {
-		self.todos.iter().filter(|&todo|!todo.completed()).collect()
+		self.walk().into_iter().filter(|&todo|!todo.completed()).collect()
 	}
 
+	// only top level titles have to be unique, subtasks are checked against their siblings
 	pub fn contains_title(&self, title: &str) -> bool {
 		self.todos.iter().any(|todo| todo.title == title)
 	}
 
-	pub fn get_todo_body(&self, idx: usize) -> Option<String> {
-		self.todos.get(idx).and_then(|todo| todo.body.clone())
+	pub fn contains_subtask_title(&self, parent: TodoId, title: &str) -> bool {
+		self.get_todo(parent).is_some_and(|todo| todo.children.iter().any(|child| child.title == title))
+	}
+
+	pub fn get_todo_body(&self, id: TodoId) -> Option<String> {
+		self.get_todo(id).and_then(|todo| todo.body.clone())
 	}
 
-	pub fn update_todo(&mut self, idx: usize, title: Option<String>, body: Option<String>) -> bool {
-        if let Some(todo) = self.todos.get_mut(idx) {
+	pub fn update_todo(&mut self, id: TodoId, title: Option<String>, body: Option<String>) -> bool {
+		self.edit_todo(id, |todo| {
 			if let Some(new_title) = title {
 				todo.title = new_title;
 			}
 			if let Some(new_body) = body {
 				todo.body = Some(new_body);
 			}
-			todo.last_edit_date = Utc::now();
-			true
+		})
+	}
+
+	// notes do not go through edit_todo, they are their own log
+	pub fn add_note(&mut self, id: TodoId, text: String) -> bool {
+		match self.get_todo_mut(id) {
+			Some(todo) => {
+				todo.notes.push(Note::now(text));
+				todo.last_edit_date = Utc::now();
+				true
+			}
+			None => false,
+		}
+	}
+
+	pub fn set_due_date(&mut self, id: TodoId, due_date: Option<DateTime<Utc>>) -> bool {
+		self.edit_todo(id, |todo| todo.due_date = due_date)
+	}
+
+	pub fn set_estimate(&mut self, id: TodoId, estimate: Option<Estimate>) -> bool {
+		self.edit_todo(id, |todo| todo.estimate = estimate)
+	}
+
+	pub fn set_priority(&mut self, id: TodoId, priority: Priority) -> bool {
+		self.edit_todo(id, |todo| todo.priority = priority)
+	}
+
+	pub fn set_tags(&mut self, id: TodoId, tags: BTreeSet<String>) -> bool {
+		self.edit_todo(id, |todo| todo.tags = tags)
+	}
+
+	// blockers that are missing (removed, or moved to another list, ids are not reused) don't block anything
+	pub fn blockers_of(&self, todo: &TodoItem) -> Vec<&TodoItem> {
+		todo.blocked_by.iter()
+			.filter_map(|&id| self.get_todo(id))
+			.filter(|blocker| !blocker.completed())
+			.collect()
+	}
+
+	pub fn is_blocked(&self, todo: &TodoItem) -> bool {
+		!self.blockers_of(todo).is_empty()
+	}
+
+	pub fn blocked_ids(&self) -> HashSet<TodoId> {
+		self.walk().into_iter().filter(|todo| self.is_blocked(todo)).map(|todo| todo.id).collect()
+	}
+
+	// whether `from` waits on `target`, directly or through other blockers
+	fn depends_on(&self, from: TodoId, target: TodoId) -> bool {
+		let mut seen = HashSet::new();
+		let mut stack = vec![from];
+		while let Some(id) = stack.pop() {
+			if id == target {
+				return true;
+			}
+			if seen.insert(id) {
+				if let Some(todo) = self.get_todo(id) {
+					stack.extend(todo.blocked_by.iter().copied());
+				}
+			}
+		}
+		false
+	}
+
+	pub fn set_blocked_by(&mut self, id: TodoId, blockers: BTreeSet<TodoId>) -> Result<(), DependencyError> {
+		for &blocker in &blockers {
+			if blocker == id {
+				return Err(DependencyError::SelfReference);
+			}
+			if self.get_todo(blocker).is_none() {
+				return Err(DependencyError::UnknownTodo(blocker));
+			}
+			if self.depends_on(blocker, id) {
+				return Err(DependencyError::Cycle(blocker));
+			}
+		}
+		if self.edit_todo(id, |todo| todo.blocked_by = blockers) {
+			Ok(())
 		} else {
-			false
+			Err(DependencyError::UnknownTodo(id))
+		}
+	}
+
+	pub fn record_pomodoro(&mut self, id: TodoId) -> bool {
+		match self.get_todo_mut(id) {
+			Some(todo) => {
+				todo.pomodoros += 1;
+				true
+			}
+			None => false,
+		}
+	}
+
+	pub fn tracked_todo(&self) -> Option<&TodoItem> {
+		self.walk().into_iter().find(|todo| todo.running_interval().is_some())
+	}
+
+	// only opens a new interval, stopping the clock on other todos is up to the caller
+	pub fn start_tracking(&mut self, id: TodoId) -> bool {
+		match self.get_todo_mut(id) {
+			Some(todo) if todo.running_interval().is_none() => {
+				todo.time_log.push(TimeInterval { start: Utc::now(), end: None });
+				true
+			}
+			_ => false,
 		}
 	}
 
-	pub fn create_list_widget(&self) -> List {
-		let items: Vec<ListItem> = self.todos
-			.iter()
-			.enumerate()
-			.map(|(i, todo)| {
-				let status = if todo.completed() { "✓" } else { " " };
-				let content = Line::from(vec![
+	// closes every running interval of the list, returns whether there was one
+	pub fn stop_tracking(&mut self) -> bool {
+		let mut stopped = false;
+		for_each_in_mut(&mut self.todos, &mut |todo| stopped |= todo.stop_tracking());
+		stopped
+	}
+
+	pub fn set_recurrence(&mut self, id: TodoId, recurrence: Option<Recurrence>) -> bool {
+		self.edit_todo(id, |todo| todo.recurrence = recurrence)
+	}
+
+	pub fn create_list_widget(&self, filter: &Filter, title: String, footer: String) -> List<'_> {
+		let items: Vec<ListItem> = self.visible_rows(filter)
+			.into_iter()
+			.filter_map(|row| self.get_todo(row.id).map(|todo| (row, todo)))
+			.map(|(row, todo)| {
+				let status = todo.status();
+				let blockers = self.blockers_of(todo);
+				let title_style = match todo.due_state() {
+					_ if status == Status::Cancelled => status.style(),
+					_ if !blockers.is_empty() => Style::default().fg(Color::DarkGray),
+					DueState::Overdue => Style::default().fg(Color::Red),
+					DueState::DueToday => Style::default().fg(Color::Yellow),
+					DueState::Upcoming | DueState::NoDueDate => Style::default(),
+				};
+				let expander = match (todo.children.is_empty(), todo.collapsed) {
+					(true, _) => "  ",
+					(false, true) => "▸ ",
+					(false, false) => "▾ ",
+				};
+				let mut spans = vec![
 					Span::styled(
-                        format!("{}: [{}] ", i, status),
+                        format!("#{}: {}{}", todo.id(), "  ".repeat(row.depth), expander),
 						Style::default().fg(Color::Yellow),
                     ),
-					Span::raw(todo.title()),
-					Span::styled(
-                        format!(" ({})", todo.creation_date().format("%d-%m-%Y %H:%M")),
-                        Style::default().fg(Color::Gray)
-                    ),
-				]);
-				ListItem::new(content)
+					Span::styled(format!("[{}] ", status.icon()), status.style().remove_modifier(Modifier::CROSSED_OUT)),
+					todo.priority().marker(),
+					Span::styled(todo.title(), title_style),
+				];
+				if todo.recurrence().is_some() {
+					spans.push(Span::styled(" ↻", Style::default().fg(Color::Magenta)));
+				}
+				if todo.running_interval().is_some() {
+					spans.push(Span::styled(
+						format!(" ⏱ {}", format_duration(todo.tracked_time())),
+						Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD),
+					));
+				} else if !todo.time_log.is_empty() {
+					spans.push(Span::styled(format!(" ⏱ {}", format_duration(todo.tracked_time())), Style::default().fg(Color::Gray)));
+				}
+				if let Some(estimate) = todo.estimate {
+					spans.push(Span::styled(format!(" ~{}", estimate.describe()), Style::default().fg(Color::Cyan)));
+				}
+				if todo.pomodoros > 0 {
+					spans.push(Span::styled(format!(" 🍅{}", todo.pomodoros), Style::default().fg(Color::LightRed)));
+				}
+				if let Some((done, total)) = todo.progress() {
+					let progress_color = if done == total { Color::Green } else { Color::Cyan };
+					spans.push(Span::styled(format!(" {}/{}", done, total), Style::default().fg(progress_color)));
+				}
+				spans.push(Span::styled(
+					format!(" ({})", todo.creation_date().format("%d-%m-%Y %H:%M")),
+					Style::default().fg(Color::Gray)
+				));
+				if let Some(due) = todo.due_date() {
+					spans.push(Span::styled(
+						format!(" due {}", due.format("%d-%m-%Y %H:%M")),
+						Style::default().fg(title_style.fg.unwrap_or(Color::Gray)),
+					));
+				}
+				for tag in todo.tags() {
+					spans.push(Span::raw(" "));
+					spans.push(tag_chip(tag));
+				}
+				for field in self.fields.iter().filter(|field| field.column) {
+					if let Some(value) = todo.field(&field.name) {
+						spans.push(Span::styled(format!(" {}: {}", field.name, value.display()), Style::default().fg(Color::LightBlue)));
+					}
+				}
+				if !blockers.is_empty() {
+					spans.push(Span::styled(
+						format!(" blocked by: {}", blockers.iter().map(|blocker| blocker.title()).collect::<Vec<_>>().join(", ")),
+						Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+					));
+				}
+				ListItem::new(Line::from(spans))
 			})
 			.collect();
 		List::new(items)
-			.block(Block::default().borders(Borders::ALL).title("Todo Items"))
+			.block(Block::default().borders(Borders::ALL).title(title).title_bottom(footer))
 			.highlight_style(Style::default().bg(Color::DarkGray))
 			.highlight_symbol("> ")
 	}
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn ids_of(list: &TodoList) -> Vec<TodoId> {
+		list.walk().iter().map(|todo| todo.id()).collect()
+	}
+
+	#[test]
+	fn legacy_todos_get_ids() {
+		// files from before ids had none, the duplicate 3 can only come from a broken file
+		let mut list: TodoList = serde_json::from_value(serde_json::json!({
+			"todos": [
+				{ "title": "a", "body": null, "creation_date": 1700000000, "last_edit_date": 1700000000 },
+				{ "id": 3, "title": "b", "body": null, "creation_date": 1700000000, "last_edit_date": 1700000000, "children": [
+					{ "title": "b.1", "body": null, "creation_date": 1700000000, "last_edit_date": 1700000000 },
+				] },
+				{ "id": 3, "title": "c", "body": null, "creation_date": 1700000000, "last_edit_date": 1700000000 },
+			],
+		})).unwrap();
+		let mut ids = IdCounter::default();
+		ids.skip_past(list.max_id());
+		list.backfill_ids(&mut HashSet::new(), &mut ids);
+		let backfilled = ids_of(&list);
+		assert!(backfilled.iter().all(|&id| id != 0));
+		assert_eq!(backfilled.iter().collect::<HashSet<_>>().len(), backfilled.len());
+		// a todo that had an id keeps it
+		assert_eq!(list.get_todo(3).unwrap().title(), "b");
+		assert!(!backfilled.contains(&ids.take()));
+	}
+
+	#[test]
+	fn ids_are_not_given_out_again() {
+		let mut list = TodoList::new();
+		let mut ids = IdCounter::default();
+		let first = list.add_todo(&mut ids, String::from("first"), None);
+		let before = list.clone();
+		let second = list.add_todo(&mut ids, String::from("second"), None);
+		let sub = list.add_subtask(&mut ids, second, String::from("sub")).unwrap();
+		list.remove_todo(second);
+		let third = list.add_todo(&mut ids, String::from("third"), None);
+		// undoing puts back the lists as they were, the counter goes on
+		list = before;
+		let fourth = list.add_todo(&mut ids, String::from("fourth"), None);
+		let given = [first, second, sub, third, fourth];
+		assert_eq!(given.iter().collect::<HashSet<_>>().len(), given.len());
+		assert_eq!(ids_of(&list), [fourth, first]);
+	}
+
+	#[test]
+	fn ids_stay_the_same_across_a_save() {
+		let mut list = TodoList::new();
+		let mut ids = IdCounter::default();
+		let parent = list.add_todo(&mut ids, String::from("parent"), None);
+		list.add_subtask(&mut ids, parent, String::from("child"));
+		let removed = list.add_todo(&mut ids, String::from("removed"), None);
+		list.remove_todo(removed);
+		list.add_todo(&mut ids, String::from("last"), None);
+		let saved = serde_json::to_string(&(&list, ids)).unwrap();
+		let (mut loaded, mut loaded_ids): (TodoList, IdCounter) = serde_json::from_str(&saved).unwrap();
+		loaded.backfill_ids(&mut HashSet::new(), &mut loaded_ids);
+		assert_eq!(ids_of(&loaded), ids_of(&list));
+		assert_eq!(loaded_ids.take(), ids.take());
+	}
+
+	fn list_of(titles: &[&str]) -> (TodoList, Vec<TodoId>) {
+		let mut list = TodoList::new();
+		let mut ids = IdCounter::default();
+		let added = titles.iter().map(|title| list.add_todo(&mut ids, title.to_string(), None)).collect();
+		(list, added)
+	}
+
+	#[test]
+	fn blocking_back_is_a_cycle() {
+		let (mut list, ids) = list_of(&["a", "b", "c"]);
+		let (a, b, c) = (ids[0], ids[1], ids[2]);
+		assert_eq!(list.set_blocked_by(a, BTreeSet::from([b])), Ok(()));
+		assert_eq!(list.set_blocked_by(b, BTreeSet::from([a])), Err(DependencyError::Cycle(a)));
+		// through another todo as well
+		assert_eq!(list.set_blocked_by(b, BTreeSet::from([c])), Ok(()));
+		assert_eq!(list.set_blocked_by(c, BTreeSet::from([a])), Err(DependencyError::Cycle(a)));
+		assert!(list.get_todo(c).unwrap().blocked_by().next().is_none());
+	}
+
+	#[test]
+	fn todo_can_not_block_itself() {
+		let (mut list, ids) = list_of(&["a", "b"]);
+		assert_eq!(list.set_blocked_by(ids[0], BTreeSet::from([ids[1], ids[0]])), Err(DependencyError::SelfReference));
+		assert!(list.get_todo(ids[0]).unwrap().blocked_by().next().is_none());
+	}
+
+	#[test]
+	fn deleted_blocker_blocks_nothing() {
+		let (mut list, ids) = list_of(&["a", "b"]);
+		let (a, b) = (ids[0], ids[1]);
+		list.set_blocked_by(a, BTreeSet::from([b])).unwrap();
+		assert!(list.is_blocked(list.get_todo(a).unwrap()));
+		list.remove_todo(b);
+		let todo = list.get_todo(a).unwrap();
+		assert!(!list.is_blocked(todo));
+		assert!(list.blockers_of(todo).is_empty());
+		assert!(list.blocked_ids().is_empty());
+		assert_eq!(list.set_blocked_by(a, BTreeSet::from([b])), Err(DependencyError::UnknownTodo(b)));
+	}
+
+	#[test]
+	fn next_occurrence_starts_without_time_or_pomodoros() {
+		let mut list = TodoList::new();
+		let mut ids = IdCounter::default();
+		let id = list.add_todo(&mut ids, String::from("water plants"), None);
+		let child = list.add_subtask(&mut ids, id, String::from("fill can")).unwrap();
+		list.set_recurrence(id, Some(Recurrence::Daily));
+		list.start_tracking(id);
+		list.record_pomodoro(id);
+		list.start_tracking(child);
+		list.record_pomodoro(child);
+		assert!(list.set_status(&mut ids, id, Status::Done, true));
+		let closed = list.get_todo(id).unwrap();
+		assert!(closed.running_interval().is_none());
+		assert!(closed.children()[0].running_interval().is_none());
+		assert_eq!(closed.pomodoros(), 1);
+		let next = list.get_todos().iter().find(|todo| todo.id() != id).unwrap();
+		assert_eq!(next.recurrence(), Some(&Recurrence::Daily));
+		for todo in [next, &next.children()[0]] {
+			assert!(todo.time_log().is_empty());
+			assert_eq!(todo.pomodoros(), 0);
+		}
+		assert!(list.tracked_todo().is_none());
+	}
+}
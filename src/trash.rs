@@ -0,0 +1,79 @@
+use chrono::{DateTime, Local, Utc};
+use serde::{Serialize, Deserialize};
+
+use crate::todo::TodoItem;
+
+// deleted todos, kept until they are restored, purged or older than the retention period
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Trash {
+	entries: Vec<TrashedTodo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedTodo {
+	// name of the list it was deleted from
+	list: String,
+	#[serde(with = "chrono::serde::ts_seconds")]
+	deleted_at: DateTime<Utc>,
+	todo: TodoItem,
+}
+
+impl TrashedTodo {
+	pub fn list(&self) -> &str {
+		&self.list
+	}
+	pub fn deleted_at(&self) -> DateTime<Local> {
+		self.deleted_at.with_timezone(&chrono::Local)
+	}
+	pub fn todo(&self) -> &TodoItem {
+		&self.todo
+	}
+	pub fn into_todo(self) -> TodoItem {
+		self.todo
+	}
+	pub fn todo_mut(&mut self) -> &mut TodoItem {
+		&mut self.todo
+	}
+}
+
+impl Trash {
+	pub fn new() -> Self {
+		Trash { entries: Vec::new() }
+	}
+
+	pub fn entries(&self) -> &[TrashedTodo] {
+		&self.entries
+	}
+
+	pub fn entries_mut(&mut self) -> &mut [TrashedTodo] {
+		&mut self.entries
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	// the most recently deleted todo comes first
+	pub fn add(&mut self, list: &str, todo: TodoItem) {
+		self.entries.insert(0, TrashedTodo { list: list.to_string(), deleted_at: Utc::now(), todo });
+	}
+
+	pub fn take(&mut self, idx: usize) -> Option<TrashedTodo> {
+		(idx < self.entries.len()).then(|| self.entries.remove(idx))
+	}
+
+	pub fn clear(&mut self) {
+		self.entries.clear();
+	}
+
+	// returns how many entries were purged
+	pub fn purge_deleted_before(&mut self, cutoff: DateTime<Utc>) -> usize {
+		let before = self.entries.len();
+		self.entries.retain(|entry| entry.deleted_at >= cutoff);
+		before - self.entries.len()
+	}
+}
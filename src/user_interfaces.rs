@@ -1,9 +1,17 @@
 use itertools::izip;
 use ratatui::{
     prelude::*,
-    widgets::{Block, Clear, Borders, Paragraph},
+    widgets::{Block, Clear, Borders, Gauge, List, ListItem, ListState, Paragraph, Tabs},
 };
-use crate::app::{App, InputMode};
+use crate::app::{App, InputMode, Prompt, View};
+use crate::backup;
+use crate::board::{ColumnKey, Grouping};
+use crate::estimate::{done_with_estimate, format_minutes, Estimate, Workload};
+use crate::fields::FieldDef;
+use crate::links::{Hyperlink, Link};
+use crate::pomodoro::{Phase, Pomodoro};
+use crate::recovery::{self, Choice};
+use crate::todo::{format_duration, tag_chip, TodoItem};
 use indoc::indoc;
 
 #[allow(clippy::many_single_char_names)]
@@ -66,27 +74,32 @@ pub fn startup_ui(frame: &mut Frame) {
 }
 
 pub fn main_ui(frame: &mut Frame, app: &mut App) {
+	app.sync_todo_list_state();
+	app.hyperlinks.clear();
 	let area = frame.area();
 	let chunks = Layout::default()
 		.direction(Direction::Vertical)
 		.constraints([
 			Constraint::Length(9),
-			Constraint::Length(5),
+			Constraint::Length(6),
 			Constraint::Length(3),
+			Constraint::Length(1),
+			Constraint::Length(if app.pomodoro.is_some() { 1 } else { 0 }),
 			Constraint::Min(1),
+			Constraint::Length(1),
 		])
 		.split(frame.area());
 	let title_paragraph = Paragraph::new(display_title())
 			.alignment(Alignment::Center)
 			.block(Block::default().borders(Borders::ALL));
 
-	let (input_help_text, style) = match app.input_mode {
+	let (input_help_text, _style) = match app.input_mode {
 		InputMode::Visual => (
 			vec![
 				Line::from(vec![
 					Span::styled("• ", Style::default().fg(Color::Yellow)),
 					Span::raw("Press "),
-					Span::styled("'N'", Style::default().fg(Color::Green)),
+					Span::styled("'n'", Style::default().fg(Color::Green)),
 					Span::raw(" to add a new todo"),
 				]),
 				Line::from(vec![
@@ -95,12 +108,48 @@ pub fn main_ui(frame: &mut Frame, app: &mut App) {
 					Span::raw("or "),
 					Span::styled("Down ", Style::default().fg(Color::LightCyan)),
 					Span::raw("arrows to select Todo"),
+					Span::raw(" ".repeat(6)),
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::styled("'a'", Style::default().fg(Color::LightCyan)),
+					Span::raw(" archive / "),
+					Span::styled("'t'", Style::default().fg(Color::LightCyan)),
+					Span::raw(" trash"),
 				]),
 				Line::from(vec![
 					Span::styled("• ", Style::default().fg(Color::Yellow)),
 					Span::raw("Press "),
-					Span::styled("'Q'", Style::default().fg(Color::Red)),
+					Span::styled("'q'", Style::default().fg(Color::Red)),
 					Span::raw(" to quit"),
+					Span::raw(" / "),
+					Span::styled("'?'", Style::default().fg(Color::LightGreen)),
+					Span::raw(" all keys"),
+					Span::raw(" / "),
+					Span::styled("'u'", Style::default().fg(Color::LightGreen)),
+					Span::raw(" undo"),
+					Span::raw(" ".repeat(6)),
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::styled("'s'", Style::default().fg(Color::LightCyan)),
+					Span::raw(" sort / "),
+					Span::styled("'f'", Style::default().fg(Color::LightCyan)),
+					Span::raw(" filter"),
+					Span::raw(" / "),
+					Span::styled("'#'", Style::default().fg(Color::LightCyan)),
+					Span::raw(" tag filter"),
+				]),
+				Line::from(vec![
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::styled("Tab ", Style::default().fg(Color::LightCyan)),
+					Span::raw("/ "),
+					Span::styled("Shift+Tab ", Style::default().fg(Color::LightCyan)),
+					Span::raw("to switch list"),
+					Span::raw(" ".repeat(6)),
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::styled("Shift+N", Style::default().fg(Color::Green)),
+					Span::raw(" new / "),
+					Span::styled("Shift+R", Style::default().fg(Color::Yellow)),
+					Span::raw(" rename / "),
+					Span::styled("Shift+X", Style::default().fg(Color::Red)),
+					Span::raw(" delete list"),
 				]),
 				],
 				Style::default().add_modifier(Modifier::RAPID_BLINK),
@@ -129,29 +178,214 @@ pub fn main_ui(frame: &mut Frame, app: &mut App) {
 					Span::raw("Press "),
 					Span::styled("'Esc'", Style::default().fg(Color::Red)),
 					Span::raw("or "),
-					Span::styled("'Q'", Style::default().fg(Color::Red)),
+					Span::styled("'q'", Style::default().fg(Color::Red)),
 					Span::raw(" to leave Select mode"),
 					Span::raw(" ".repeat(6)), 
 					Span::styled("• ", Style::default().fg(Color::Yellow)),
 					Span::raw("Press "),
-					Span::styled("'D'", Style::default().fg(Color::Red)),
+					Span::styled("'d'", Style::default().fg(Color::Red)),
 					Span::raw(" to delete a todo"),
+					Span::raw(" / "),
+					Span::styled("'x'", Style::default().fg(Color::LightCyan)),
+					Span::raw(" archive it"),
+					Span::raw(" / "),
+					Span::styled("'u'", Style::default().fg(Color::LightGreen)),
+					Span::raw(" undo"),
+					Span::raw(" ".repeat(6)),
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::styled("'+'", Style::default().fg(Color::LightRed)),
+					Span::raw(" / "),
+					Span::styled("'-'", Style::default().fg(Color::Blue)),
+					Span::raw(" priority"),
 				]),
 				Line::from(vec![
 					Span::styled("• ", Style::default().fg(Color::Yellow)),
 					Span::raw("Press "),
 					Span::styled("Enter ", Style::default().fg(Color::Green)),
 					Span::raw("to select hovered Todo"),
+					Span::raw(" ".repeat(6)),
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::styled("'s'", Style::default().fg(Color::LightCyan)),
+					Span::raw(" sort / "),
+					Span::styled("'f'", Style::default().fg(Color::LightCyan)),
+					Span::raw(" filter"),
+					Span::raw(" / "),
+					Span::styled("'#'", Style::default().fg(Color::LightCyan)),
+					Span::raw(" tag filter"),
 				]),
 				Line::from(vec![
 					Span::styled("• ", Style::default().fg(Color::Yellow)),
 					Span::raw("Press "),
-					Span::styled("'C'", Style::default().fg(Color::Blue)),
+					Span::styled("'c'", Style::default().fg(Color::Blue)),
 					Span::raw(" to complete a todo"),
-				])
+					Span::raw(" ".repeat(6)),
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::styled("Space", Style::default().fg(Color::Cyan)),
+					Span::raw(" cycle status / "),
+					Span::styled("'1'-'6'", Style::default().fg(Color::Cyan)),
+					Span::raw(" set status"),
+				]),
+				Line::from(vec![
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::raw("Press "),
+					Span::styled("'?'", Style::default().fg(Color::LightGreen)),
+					Span::raw(" to show all keys"),
+					Span::raw(" ".repeat(6)),
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::styled("'v'", Style::default().fg(Color::LightCyan)),
+					Span::raw(" board view / "),
+					Span::styled("'<'", Style::default().fg(Color::LightCyan)),
+					Span::raw(" "),
+					Span::styled("'>'", Style::default().fg(Color::LightCyan)),
+					Span::raw(" move card"),
+				]),
+			],
+			Style::default(),
+		),
+		InputMode::Archive => (
+			vec![
+				Line::from(vec![
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::raw("Type to search the archive"),
+					Span::raw(" ".repeat(6)),
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::styled("Up ", Style::default().fg(Color::LightCyan)),
+					Span::raw("or "),
+					Span::styled("Down ", Style::default().fg(Color::LightCyan)),
+					Span::raw("arrows to select"),
+				]),
+				Line::from(vec![
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::raw("Press "),
+					Span::styled("Enter ", Style::default().fg(Color::Green)),
+					Span::raw("to restore the selected todo"),
+				]),
+				Line::from(vec![
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::raw("Press "),
+					Span::styled("'Esc'", Style::default().fg(Color::Red)),
+					Span::raw(" to leave the archive"),
+				]),
+			],
+			Style::default(),
+		),
+		InputMode::Trash => (
+			vec![
+				Line::from(vec![
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::styled("Up ", Style::default().fg(Color::LightCyan)),
+					Span::raw("or "),
+					Span::styled("Down ", Style::default().fg(Color::LightCyan)),
+					Span::raw("arrows to select"),
+					Span::raw(" ".repeat(6)),
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::raw("Press "),
+					Span::styled("Enter ", Style::default().fg(Color::Green)),
+					Span::raw("or "),
+					Span::styled("'r'", Style::default().fg(Color::Green)),
+					Span::raw(" to restore the selected todo"),
+				]),
+				Line::from(vec![
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::raw("Press "),
+					Span::styled("'p'", Style::default().fg(Color::Red)),
+					Span::raw(" to delete it for good / "),
+					Span::styled("Shift+E", Style::default().fg(Color::Red)),
+					Span::raw(" to empty the trash"),
+				]),
+				Line::from(vec![
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::raw("Press "),
+					Span::styled("'Esc'", Style::default().fg(Color::Red)),
+					Span::raw(" to leave the trash"),
+				]),
+			],
+			Style::default(),
+		),
+		InputMode::Backups => (
+			vec![
+				Line::from(vec![
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::styled("Up ", Style::default().fg(Color::LightCyan)),
+					Span::raw("or "),
+					Span::styled("Down ", Style::default().fg(Color::LightCyan)),
+					Span::raw("arrows to select"),
+					Span::raw(" ".repeat(6)),
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::raw("Press "),
+					Span::styled("Enter ", Style::default().fg(Color::Green)),
+					Span::raw("or "),
+					Span::styled("'r'", Style::default().fg(Color::Green)),
+					Span::raw(" to restore the selected backup"),
+				]),
+				Line::from(vec![
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::raw("A backup is taken every time the app starts, "),
+					Span::styled("Shift+B", Style::default().fg(Color::LightCyan)),
+					Span::raw(" from the list sets how many are kept"),
+				]),
+				Line::from(vec![
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::raw("Press "),
+					Span::styled("'Esc'", Style::default().fg(Color::Red)),
+					Span::raw(" to leave the backups"),
+				]),
+			],
+			Style::default(),
+		),
+		InputMode::Recovery => (
+			vec![
+				Line::from(vec![
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::styled("Up ", Style::default().fg(Color::LightCyan)),
+					Span::raw("or "),
+					Span::styled("Down ", Style::default().fg(Color::LightCyan)),
+					Span::raw("arrows to pick what to continue with"),
+					Span::raw(" ".repeat(6)),
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::raw("Press "),
+					Span::styled("Enter ", Style::default().fg(Color::Green)),
+					Span::raw("to continue with it, it is saved right away"),
+				]),
+				Line::from(vec![
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::raw("Press "),
+					Span::styled("'q'", Style::default().fg(Color::Red)),
+					Span::raw(" to quit and decide on the next start, the broken file is put back as it was"),
+				]),
+			],
+			Style::default(),
+		),
+		InputMode::Confirm(_) => (
+			vec![
+				Line::from(vec![
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::raw("Press "),
+					Span::styled("'y'", Style::default().fg(Color::Red)),
+					Span::raw(" to confirm or "),
+					Span::styled("'n'", Style::default().fg(Color::Green)),
+					Span::raw(" to cancel"),
+				]),
 			],
 			Style::default(),
-		)
+		),
+		InputMode::Prompt(_) => (
+			vec![
+				Line::from(vec![
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::raw("Press "),
+					Span::styled("'Esc'", Style::default().fg(Color::Green)),
+					Span::raw(" to cancel"),
+				]),
+				Line::from(vec![
+					Span::styled("• ", Style::default().fg(Color::Yellow)),
+					Span::raw("Press "),
+					Span::styled("Enter ", Style::default().fg(Color::Yellow)),
+					Span::raw("to save"),
+				]),
+			],
+			Style::default(),
+		),
 	};
 	let input_help = Paragraph::new(input_help_text)
 			.block(Block::default().borders(Borders::ALL).title("Input Help"))
@@ -161,15 +395,37 @@ pub fn main_ui(frame: &mut Frame, app: &mut App) {
 	let user_input = Paragraph::new(app.input.as_str())
 		.style(match app.input_mode {
 			InputMode::Visual => Style::default(),
-			InputMode::Input => Style::default().fg(Color::Yellow),
+			InputMode::Input | InputMode::Prompt(_) | InputMode::Archive => Style::default().fg(Color::Yellow),
 			InputMode::Select => Style::default(),
 			InputMode::Popup | InputMode::PopupInput => Style::default(),
+			InputMode::Trash | InputMode::Backups | InputMode::Recovery | InputMode::Confirm(_) => Style::default(),
 		})
-		.block(Block::bordered().title("Input"))
+		.block(Block::bordered().title(match (app.input_mode, app.new_todo_parent) {
+			(InputMode::Prompt(Prompt::MoveToList), _) => format!(
+				"{} ({})",
+				Prompt::MoveToList.title(),
+				app.workspace.lists().iter().map(|list| list.name()).collect::<Vec<_>>().join(", ")
+			),
+			(InputMode::Prompt(Prompt::EditField), _) => format!(
+				"{} ({})",
+				Prompt::EditField.title(),
+				app.workspace.active_list().fields().iter()
+					.map(|field| format!("{}: {}", field.name, field.kind.label()))
+					.collect::<Vec<_>>()
+					.join(", ")
+			),
+			(InputMode::Prompt(prompt), _) => prompt.title().to_string(),
+			(InputMode::Archive, _) => String::from("Search the archive"),
+			(InputMode::Input, Some(parent)) => format!(
+				"New subtask of '{}'",
+				app.workspace.active_list().get_todo(parent).map(|todo| todo.title()).unwrap_or_default()
+			),
+			_ => String::from("Input"),
+		}))
 		.add_modifier(Modifier::RAPID_BLINK);
 	match app.input_mode {
 		InputMode::Visual => {}
-		InputMode::Input => {
+		InputMode::Input | InputMode::Prompt(_) | InputMode::Archive => {
 			#[allow(clippy::cast_possible_truncation)]
 			frame.set_cursor_position(Position {
 				x: chunks[2].x + app.cursor_index as u16 + 1,
@@ -177,33 +433,129 @@ pub fn main_ui(frame: &mut Frame, app: &mut App) {
 			});
 		}
 		InputMode::Select | InputMode::Popup | InputMode::PopupInput => {}
+		InputMode::Trash | InputMode::Backups | InputMode::Recovery | InputMode::Confirm(_) => {}
 	}
-	let todo_list = app.todo_list.create_list_widget();
+	let list_title = format!("Todo Items (sort: {}, filter: {})", app.sort_order.label(app.workspace.active_list().fields()), app.filter.label());
+	let remaining = Workload::remaining(app.workspace.active_list(), &app.filter);
+	let list_footer = format!("Remaining: {}", remaining.describe());
+	let todo_list = app.workspace.active_list().create_list_widget(&app.filter, list_title, list_footer);
+	let list_tabs = Tabs::new(app.workspace.lists().iter().map(|list| list.name().to_string()))
+		.select(app.workspace.active_index())
+		.style(Style::default().fg(Color::Gray))
+		.highlight_style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD | Modifier::REVERSED));
+	// changes are written on their own shortly after they are made
+	let save_state = if app.recovery.is_some() {
+		Span::styled("● recovery", Style::default().fg(Color::Red))
+	} else if app.has_unsaved_changes() {
+		Span::styled("● unsaved", Style::default().fg(Color::Yellow))
+	} else {
+		Span::styled("✓ saved", Style::default().fg(Color::Green))
+	};
+	let tab_chunks = Layout::horizontal([Constraint::Min(0), Constraint::Length(10)]).split(chunks[3]);
 
     frame.render_widget(title_paragraph, chunks[0]);
 	frame.render_widget(input_help, chunks[1]);
 	frame.render_widget(user_input, chunks[2]);
-	if app.input_mode == InputMode::Select {
-		frame.render_stateful_widget(todo_list, chunks[3], &mut app.todo_list_state);
+	frame.render_widget(list_tabs, tab_chunks[0]);
+	frame.render_widget(Paragraph::new(save_state).right_aligned(), tab_chunks[1]);
+	if let Some(pomodoro) = &app.pomodoro {
+		frame.render_widget(pomodoro_gauge(app, pomodoro), chunks[4]);
+	}
+	let confirming = matches!(app.input_mode, InputMode::Confirm(_));
+	if app.input_mode == InputMode::Recovery {
+		render_recovery(frame, app, chunks[5]);
+	} else if app.input_mode == InputMode::Archive {
+		render_archive(frame, app, chunks[5]);
+	} else if app.input_mode == InputMode::Trash || (confirming && app.confirm_return_mode == InputMode::Trash) {
+		render_trash(frame, app, chunks[5]);
+	} else if app.input_mode == InputMode::Backups || (confirming && app.confirm_return_mode == InputMode::Backups) {
+		render_backups(frame, app, chunks[5]);
+	} else if app.view == View::Board {
+		render_board(frame, app, chunks[5]);
+	} else if matches!(app.input_mode, InputMode::Select | InputMode::Prompt(_) | InputMode::Confirm(_)) {
+		frame.render_stateful_widget(todo_list, chunks[5], &mut app.todo_list_state);
 	} else {
-		frame.render_widget(todo_list, chunks[3]);
+		frame.render_widget(todo_list, chunks[5]);
+	}
+	let footer = match (app.current_notice(), &app.tracking) {
+		(Some(notice), _) if notice.alert => Line::styled(
+			notice.message.as_str(),
+			Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD | Modifier::SLOW_BLINK),
+		),
+		(Some(notice), _) => Line::styled(notice.message.as_str(), Style::default().fg(Color::Yellow)),
+		(None, Some(tracking)) => {
+			let title = app.workspace.lists()[tracking.list].get_todo(tracking.todo).map(|todo| todo.title()).unwrap_or_default();
+			let elapsed = chrono::Duration::from_std(tracking.timer.elapsed()).unwrap_or_default();
+			Line::styled(format!("⏱ Tracking '{}' for {}", title, format_duration(elapsed)), Style::default().fg(Color::LightGreen))
+		}
+		(None, None) => Line::default(),
+	};
+	let footer = Paragraph::new(footer);
+	frame.render_widget(footer, chunks[6]);
+
+	if let InputMode::Confirm(confirm) = app.input_mode {
+		let question = Paragraph::new(vec![
+			Line::raw(app.confirm_message(confirm)),
+			Line::raw(""),
+			Line::from(vec![
+				Span::styled("y", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+				Span::raw(" yes / "),
+				Span::styled("n", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+				Span::raw(" no"),
+			]),
+		])
+			.alignment(Alignment::Center)
+			.wrap(ratatui::widgets::Wrap {trim: true})
+			.block(Block::bordered().title("Confirm").border_style(Style::default().fg(Color::Red)));
+		let area = centered_rect(40, 20, area);
+		frame.render_widget(Clear, area);
+		frame.render_widget(question, area);
+	}
+
+	if app.show_report {
+		let report = Paragraph::new(estimate_report(app))
+			.block(Block::bordered().title("Estimates ('e' to close)"));
+		let area = centered_rect(70, 70, area);
+		frame.render_widget(Clear, area);
+		frame.render_widget(report, area);
+	}
+
+	if app.show_keys {
+		let keys = Paragraph::new(keymap())
+			.block(Block::bordered().title("Keys ('?' to close)"));
+		let area = centered_rect(60, 80, area);
+		frame.render_widget(Clear, area);
+		frame.render_widget(keys, area);
 	}
 
 	if app.show_todo_popup {
 		match app.input_mode {
 			InputMode::Popup => {
-				let mut popup_text = String::new();
-				if app.todo_list.get_todo_body(app.todo_list_index) == None {
-					popup_text = String::from("Press i to start writing Todo's details");
-				} else {
-					popup_text = String::from(app.todo_list.get_todo_body(app.todo_list_index).unwrap_or_else(||"Empty body".to_string()));
-				}
+				let todo = app.selected_todo.and_then(|id| app.workspace.active_list().get_todo(id));
+				let (popup_text, title) = match app.show_todo_history {
+					true => (todo.map(todo_history).unwrap_or_default(), "History ('h' back to the body)"),
+					false => (
+						todo.map(|todo| todo_details(todo, app.workspace.active_list().fields())).unwrap_or_default(),
+						"Todo Body ('h' history, 'n' add a note, 'e' edit a field)",
+					),
+				};
+				let links = match app.show_todo_history {
+					true => Vec::new(),
+					false => app.selected_links().to_vec(),
+				};
+				let block = Block::bordered().title(title);
+				let area = centered_rect(60, if app.show_todo_history { 60 } else { 30 }, area);
+				let [text_area, links_area] = Layout::vertical([
+					Constraint::Min(0),
+					Constraint::Length(if links.is_empty() { 0 } else { links.len() as u16 + 1 }),
+				]).areas(block.inner(area));
 				let popup = Paragraph::new(popup_text)
 				.style(Style::default())
-				.block(Block::bordered().title("Todo Body"));
-				let area = centered_rect(60, 20, area);
+				.wrap(ratatui::widgets::Wrap {trim: false});
 				frame.render_widget(Clear, area);
-				frame.render_widget(popup, area);
+				frame.render_widget(block, area);
+				frame.render_widget(popup, text_area);
+				app.hyperlinks = render_links(frame, &links, app.selected_link, links_area);
 			},
 			InputMode::PopupInput => {
 				let popup = Paragraph::new(app.popup_input.as_str())
@@ -223,6 +575,482 @@ pub fn main_ui(frame: &mut Frame, app: &mut App) {
 	}
 }
 
+// one column per status or tag, the selected card is highlighted in the column it was selected in
+fn render_board(frame: &mut Frame, app: &App, area: Rect) {
+	let columns = app.board_columns();
+	let block = Block::bordered().title(format!(
+		"Board by {} (filter: {})",
+		app.board_grouping.label(),
+		app.filter.label()
+	));
+	let inner = block.inner(area);
+	frame.render_widget(block, area);
+	if columns.is_empty() {
+		return;
+	}
+	let selected = match app.input_mode {
+		InputMode::Select | InputMode::Prompt(_) | InputMode::Popup | InputMode::PopupInput | InputMode::Confirm(_) => app.selected_card(&columns),
+		_ => None,
+	};
+	let list = app.workspace.active_list();
+	let areas = Layout::horizontal(vec![Constraint::Ratio(1, columns.len() as u32); columns.len()]).split(inner);
+	for (col, (column, &column_area)) in columns.iter().zip(areas.iter()).enumerate() {
+		let title_style = match &column.key {
+			ColumnKey::Status(status) => status.style().remove_modifier(Modifier::CROSSED_OUT),
+			_ => Style::default().fg(Color::Yellow),
+		};
+		let cards: Vec<ListItem> = column.todos.iter()
+			.filter_map(|&id| list.get_todo(id))
+			.map(|todo| {
+				let mut spans = vec![
+					Span::styled(format!("[{}] ", todo.status().icon()), todo.status().style().remove_modifier(Modifier::CROSSED_OUT)),
+					todo.priority().marker(),
+					Span::raw(todo.title()),
+				];
+				if app.board_grouping == Grouping::Tag {
+					spans.push(Span::styled(format!(" ({})", todo.status().label()), Style::default().fg(Color::DarkGray)));
+				}
+				ListItem::new(Line::from(spans))
+			})
+			.collect();
+		let cards = List::new(cards)
+			.block(Block::default()
+				.borders(Borders::TOP | Borders::RIGHT)
+				.title(Span::styled(format!("{} ({})", column.key.label(), column.todos.len()), title_style)))
+			.highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+			.highlight_symbol(">");
+		let mut state = ListState::default();
+		state.select(selected.filter(|&(selected_col, _)| selected_col == col).map(|(_, row)| row));
+		frame.render_stateful_widget(cards, column_area, &mut state);
+	}
+}
+
+// a row of the archive or trash: the todo, where it came from and when it was taken out
+fn stored_todo_item<'a>(todo: &'a TodoItem, list: &'a str, when: String) -> ListItem<'a> {
+	let mut spans = vec![
+		Span::styled(format!("[{}] ", todo.status().icon()), todo.status().style().remove_modifier(Modifier::CROSSED_OUT)),
+		todo.priority().marker(),
+		Span::raw(todo.title()),
+		Span::styled(format!(" from {}", list), Style::default().fg(Color::Cyan)),
+		Span::styled(format!(" ({})", when), Style::default().fg(Color::DarkGray)),
+	];
+	if !todo.children().is_empty() {
+		spans.push(Span::styled(format!(" +{} subtasks", todo.children().len()), Style::default().fg(Color::DarkGray)));
+	}
+	for tag in todo.tags() {
+		spans.push(Span::raw(" "));
+		spans.push(tag_chip(tag));
+	}
+	ListItem::new(Line::from(spans))
+}
+
+fn render_trash(frame: &mut Frame, app: &mut App, area: Rect) {
+	let trash = app.workspace.trash();
+	let retention = app.workspace.settings().trash_retention_days;
+	let items: Vec<ListItem> = trash.entries().iter()
+		.map(|entry| stored_todo_item(
+			entry.todo(),
+			entry.list(),
+			format!("deleted {}", entry.deleted_at().format("%d-%m-%Y %H:%M")),
+		))
+		.collect();
+	let list = List::new(items)
+		.block(Block::bordered().title(format!("Trash ({}, kept for {} days)", trash.len(), retention)))
+		.highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+		.highlight_symbol("> ");
+	if trash.is_empty() {
+		app.trash_state.select(None);
+	} else if app.trash_state.selected().is_none() {
+		app.trash_state.select(Some(0));
+	}
+	frame.render_stateful_widget(list, area, &mut app.trash_state);
+}
+
+fn render_backups(frame: &mut Frame, app: &mut App, area: Rect) {
+	let items: Vec<ListItem> = app.backups.iter()
+		.map(|backup| ListItem::new(Line::from(vec![
+			Span::raw(backup.taken_at().format("%d-%m-%Y %H:%M:%S").to_string()),
+			Span::styled(format!("  {:>9}", backup::format_size(backup.size())), Style::default().fg(Color::Cyan)),
+			Span::styled(format!("  {}", backup.file_name()), Style::default().fg(Color::DarkGray)),
+		])))
+		.collect();
+	let list = List::new(items)
+		.block(Block::bordered().title(format!(
+			"Backups in {} ({}, keeping {})",
+			app.paths.backup_dir().display(),
+			app.backups.len(),
+			app.workspace.settings().backup_count
+		)))
+		.highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+		.highlight_symbol("> ");
+	frame.render_stateful_widget(list, area, &mut app.backup_state);
+}
+
+// what went wrong with the data file above, what can be continued with below
+fn render_recovery(frame: &mut Frame, app: &mut App, area: Rect) {
+	let Some(recovery) = &app.recovery else {
+		return;
+	};
+	let mut lines = vec![
+		Line::from(vec![
+			Span::raw("Reading "),
+			Span::styled(app.paths.data_file().display().to_string(), Style::default().fg(Color::Cyan)),
+			Span::raw(" failed: "),
+			Span::styled(recovery.error(), Style::default().fg(Color::Red)),
+		]),
+	];
+	if let Some((excerpt, offset)) = recovery.excerpt() {
+		lines.push(Line::styled(format!("  {}", excerpt), Style::default().fg(Color::Gray)));
+		lines.push(Line::styled(format!("  {}^", " ".repeat(offset)), Style::default().fg(Color::Red)));
+	}
+	lines.push(Line::from(vec![
+		Span::raw("It was moved to "),
+		Span::styled(recovery.moved_to().display().to_string(), Style::default().fg(Color::Cyan)),
+		Span::raw(" so nothing is written over it. Pick what to continue with below."),
+	]));
+	let details = Paragraph::new(lines)
+		.block(Block::bordered().title("The todos could not be read").border_style(Style::default().fg(Color::Red)))
+		.wrap(ratatui::widgets::Wrap { trim: false });
+
+	let items: Vec<ListItem> = recovery.choices().iter()
+		.map(|&choice| ListItem::new(match choice {
+			Choice::Salvaged => {
+				let salvaged = recovery.salvaged().map(recovery::todo_count).unwrap_or(0);
+				let lists = recovery.salvaged().map_or(0, |workspace| workspace.lists().len());
+				Line::from(vec![
+					Span::raw("Continue with what could be salvaged"),
+					Span::styled(
+						format!("  {} todos in {} list{}", salvaged, lists, if lists == 1 { "" } else { "s" }),
+						Style::default().fg(Color::Green),
+					),
+				])
+			}
+			Choice::Backup(idx) => {
+				let backup = &recovery.backups()[idx];
+				Line::from(vec![
+					Span::raw(format!("Continue with the backup of {}", backup.taken_at().format("%d-%m-%Y %H:%M:%S"))),
+					Span::styled(format!("  {:>9}", backup::format_size(backup.size())), Style::default().fg(Color::Cyan)),
+					Span::styled(format!("  {}", backup.file_name()), Style::default().fg(Color::DarkGray)),
+				])
+			}
+			Choice::Fresh => Line::raw("Start fresh with an empty list"),
+		}))
+		.collect();
+	let choices = List::new(items)
+		.block(Block::bordered().title("Continue with"))
+		.highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+		.highlight_symbol("> ");
+
+	let chunks = Layout::vertical([Constraint::Length(7), Constraint::Min(3)]).split(area);
+	frame.render_widget(details, chunks[0]);
+	frame.render_stateful_widget(choices, chunks[1], &mut app.recovery_state);
+}
+
+fn render_archive(frame: &mut Frame, app: &mut App, area: Rect) {
+	let results = app.archive_results();
+	let items: Vec<ListItem> = results.iter()
+		.map(|&idx| &app.archive.entries()[idx])
+		.map(|entry| stored_todo_item(
+			entry.todo(),
+			entry.list(),
+			format!("archived {}", entry.archived_at().format("%d-%m-%Y %H:%M")),
+		))
+		.collect();
+	let archive = List::new(items)
+		.block(Block::bordered().title(format!("Archive ({} of {})", results.len(), app.archive.len())))
+		.highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+		.highlight_symbol("> ");
+	if results.is_empty() {
+		app.archive_state.select(None);
+	} else if app.archive_state.selected().is_none() {
+		app.archive_state.select(Some(0));
+	}
+	frame.render_stateful_widget(archive, area, &mut app.archive_state);
+}
+
+fn pomodoro_gauge<'a>(app: &'a App, pomodoro: &Pomodoro) -> Gauge<'a> {
+	let title = app.workspace.lists()
+		.get(pomodoro.list)
+		.and_then(|list| list.get_todo(pomodoro.todo))
+		.map(|todo| todo.title())
+		.unwrap_or_default();
+	let remaining = pomodoro.remaining().as_secs();
+	let color = match pomodoro.phase() {
+		Phase::Focus => Color::LightRed,
+		Phase::ShortBreak | Phase::LongBreak => Color::LightGreen,
+	};
+	Gauge::default()
+		.gauge_style(Style::default().fg(color).bg(Color::DarkGray))
+		.ratio(pomodoro.progress())
+		.label(format!(
+			"🍅 {} on '{}': {:02}:{:02} left ({} of {} sessions done)",
+			pomodoro.phase().label(),
+			title,
+			remaining / 60,
+			remaining % 60,
+			pomodoro.focus_sessions() % pomodoro.sessions_before_long_break().max(1),
+			pomodoro.sessions_before_long_break(),
+		))
+}
+
+// every key of the list and select modes, the help box only has room for the common ones
+const KEYMAP: &[(&str, &str)] = &[
+	("List", ""),
+	("n", "new todo"),
+	("Up / Down", "select todos"),
+	("Tab / Shift+Tab", "switch list"),
+	("Shift+N / R / X", "new / rename / delete list"),
+	("Shift+P", "pomodoro settings"),
+	("s / f / #", "sort / filter / tag filter"),
+	("%", "filter by a custom field"),
+	("Shift+F", "custom fields of the list"),
+	("a", "browse the archive"),
+	("t", "browse the trash"),
+	("b", "browse the backups"),
+	("Shift+B", "number of backups to keep"),
+	("Shift+T", "days to keep deleted todos"),
+	("Shift+A", "archive closed todos after N days"),
+	("e", "estimates against tracked time"),
+	("v", "list / board view"),
+	("Shift+V", "board by status / tag"),
+	("u", "undo"),
+	("Ctrl+R / Shift+U", "redo"),
+	("q / Ctrl+C", "save and quit"),
+	("Select", ""),
+	("Esc / q", "back to the list"),
+	("Enter", "open the todo"),
+	("h (in the todo)", "history of its changes"),
+	("e (in the todo)", "edit a custom field"),
+	("n (in the todo)", "add a note"),
+	("l / o (in the todo)", "select / open a link"),
+	("o", "open the first link"),
+	("a", "add a subtask"),
+	("Left / Right", "collapse / expand"),
+	("c", "complete / reopen"),
+	("Shift+C", "complete with subtasks"),
+	("Space", "cycle status"),
+	("1", "status: todo"),
+	("2", "status: in progress"),
+	("3", "status: blocked"),
+	("4", "status: waiting"),
+	("5", "status: done"),
+	("6", "status: cancelled"),
+	("d", "delete to the trash"),
+	("x", "archive a done or cancelled todo"),
+	("t", "due date"),
+	("+ / -", "raise / lower priority"),
+	("g", "tags"),
+	("Shift+E", "estimate, also in the todo"),
+	("r", "repeat"),
+	("m", "move to list"),
+	("b", "blocked by"),
+	("w", "start / stop tracking time"),
+	("p", "start / stop pomodoro"),
+	("s / f / #", "sort / filter / tag filter"),
+	("Board", ""),
+	("Arrows", "move between cards and columns"),
+	("< / >", "move the card to the next column"),
+	("Archive", ""),
+	("typing", "search"),
+	("Enter", "restore to its list"),
+	("Trash", ""),
+	("Enter / r", "restore to its list"),
+	("p / Delete", "delete for good"),
+	("Shift+E", "empty the trash"),
+	("Backups", ""),
+	("Enter / r", "restore, 'u' undoes it"),
+	("Recovery", ""),
+	("Enter", "continue with the salvaged todos, a backup or nothing"),
+	("q", "quit and decide on the next start"),
+];
+
+fn keymap() -> Vec<Line<'static>> {
+	KEYMAP.iter()
+		.map(|&(key, action)| match action {
+			"" => Line::styled(key, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+			_ => Line::from(vec![
+				Span::styled(format!("  {:<18}", key), Style::default().fg(Color::LightCyan)),
+				Span::raw(action),
+			]),
+		})
+		.collect()
+}
+
+// done todos with an estimate next to the time they took, then what is left for the current filter
+fn estimate_report(app: &App) -> Vec<Line<'_>> {
+	let list = app.workspace.active_list();
+	let rows = done_with_estimate(list);
+	let mut lines = vec![Line::styled(format!("Done in {}", list.name()), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))];
+	if rows.is_empty() {
+		lines.push(Line::styled("  no done todo has an estimate yet", Style::default().fg(Color::Gray)));
+	}
+	for row in &rows {
+		let mut spans = vec![
+			Span::raw(format!("  {:<30} ", row.todo.title().chars().take(30).collect::<String>())),
+			Span::styled(format!("~{:<8}", row.estimate.describe()), Style::default().fg(Color::Cyan)),
+			Span::raw(format!(" took {:<8}", format_minutes(row.actual.num_minutes()))),
+		];
+		if let Some(overrun) = row.overrun() {
+			let (text, color) = match overrun {
+				0 => (String::from("on time"), Color::Green),
+				minutes if minutes > 0 => (format!("{} over", format_minutes(minutes)), Color::Red),
+				minutes => (format!("{} under", format_minutes(-minutes)), Color::Green),
+			};
+			spans.push(Span::styled(format!(" {}", text), Style::default().fg(color)));
+		}
+		lines.push(Line::from(spans));
+	}
+
+	let (estimated, actual) = rows.iter()
+		.filter_map(|row| match row.estimate {
+			Estimate::Minutes(minutes) => Some((i64::from(minutes), row.actual.num_minutes())),
+			Estimate::Points(_) => None,
+		})
+		.fold((0, 0), |(estimated, actual), (minutes, took)| (estimated + minutes, actual + took));
+	let (points, point_minutes) = rows.iter()
+		.filter_map(|row| match row.estimate {
+			Estimate::Points(points) => Some((i64::from(points), row.actual.num_minutes())),
+			Estimate::Minutes(_) => None,
+		})
+		.fold((0, 0), |(points, actual), (estimate, took)| (points + estimate, actual + took));
+	lines.push(Line::raw(""));
+	if estimated > 0 {
+		lines.push(Line::from(vec![
+			Span::styled("Time estimates: ", Style::default().fg(Color::Yellow)),
+			Span::raw(format!(
+				"{} estimated, {} tracked ({}% of the estimate)",
+				format_minutes(estimated),
+				format_minutes(actual),
+				actual * 100 / estimated
+			)),
+		]));
+	}
+	if points > 0 {
+		lines.push(Line::from(vec![
+			Span::styled("Story points: ", Style::default().fg(Color::Yellow)),
+			Span::raw(format!(
+				"{}pt took {}, {} per point",
+				points,
+				format_minutes(point_minutes),
+				format_minutes(point_minutes / points)
+			)),
+		]));
+	}
+	lines.push(Line::from(vec![
+		Span::styled(format!("Remaining (filter: {}): ", app.filter.label()), Style::default().fg(Color::Yellow)),
+		Span::raw(Workload::remaining(list, &app.filter).describe()),
+	]));
+	lines
+}
+
+// one link per line under the body of the todo, returns where the main loop writes them as hyperlinks
+fn render_links(frame: &mut Frame, links: &[Link], selected: usize, area: Rect) -> Vec<Hyperlink> {
+	if links.is_empty() || area.height == 0 {
+		return Vec::new();
+	}
+	frame.render_widget(Line::styled("Links ('l' next, 'o' open)", Style::default().fg(Color::Yellow)), area);
+	let mut hyperlinks = Vec::new();
+	for (idx, link) in links.iter().enumerate().take(area.height as usize - 1) {
+		let row = Rect { y: area.y + idx as u16 + 1, height: 1, ..area };
+		let marker = if idx == selected { "> " } else { "  " };
+		let style = match idx == selected {
+			true => Style::default().fg(Color::LightCyan).add_modifier(Modifier::UNDERLINED),
+			false => Style::default().fg(Color::LightBlue),
+		};
+		frame.render_widget(Line::from(vec![Span::raw(marker), Span::styled(link.label(), style)]), row);
+		let text: String = link.label().chars().take(row.width.saturating_sub(2) as usize).collect();
+		hyperlinks.push(Hyperlink { x: row.x + 2, y: row.y, text, style, target: link.target() });
+	}
+	hyperlinks
+}
+
+// body of the popup: the todo's body followed by what is known about it
+fn todo_details<'a>(todo: &'a TodoItem, fields: &[FieldDef]) -> Vec<Line<'a>> {
+	let mut lines: Vec<Line> = match todo.body() {
+		Some(body) => body.lines().map(Line::raw).collect(),
+		None => vec![Line::styled("Press i to start writing Todo's details", Style::default().fg(Color::Gray))],
+	};
+	if !todo.notes().is_empty() {
+		lines.push(Line::raw(""));
+		lines.push(Line::styled("Notes", Style::default().fg(Color::Yellow)));
+		for note in todo.notes() {
+			lines.push(Line::from(vec![
+				Span::styled(format!("{}  ", note.at().format("%d-%m-%Y %H:%M")), Style::default().fg(Color::Gray)),
+				Span::raw(note.text()),
+			]));
+		}
+	}
+	lines.push(Line::raw(""));
+	let mut status = vec![
+		Span::styled("Status: ", Style::default().fg(Color::Yellow)),
+		Span::styled(todo.status().label(), todo.status().style()),
+	];
+	if let Some(closed_at) = todo.closed_at() {
+		status.push(Span::raw(format!(" since {}", closed_at.format("%d-%m-%Y %H:%M"))));
+	}
+	lines.push(Line::from(status));
+	for field in fields {
+		lines.push(Line::from(vec![
+			Span::styled(format!("{}: ", field.name), Style::default().fg(Color::Yellow)),
+			match todo.field(&field.name) {
+				Some(value) => Span::raw(value.display()),
+				None => Span::styled("-", Style::default().fg(Color::DarkGray)),
+			},
+		]));
+	}
+	if !todo.time_log().is_empty() {
+		lines.push(Line::raw(""));
+		let mut time_spent = vec![
+			Span::styled("Time spent: ", Style::default().fg(Color::Yellow)),
+			Span::raw(format!(
+				"{} over {} session{}",
+				format_duration(todo.tracked_time()),
+				todo.time_log().len(),
+				if todo.time_log().len() == 1 { "" } else { "s" }
+			)),
+		];
+		if let Some(running) = todo.running_interval() {
+			time_spent.push(Span::styled(
+				format!(" (running since {})", running.start().format("%H:%M")),
+				Style::default().fg(Color::LightGreen),
+			));
+		}
+		lines.push(Line::from(time_spent));
+	}
+	lines
+}
+
+// status changes and field edits of the todo, the latest first
+fn todo_history(todo: &TodoItem) -> Vec<Line<'_>> {
+	let single_line = |value: &str| match value {
+		"" => String::from("(none)"),
+		value => format!("'{}'", value.replace('\n', " ⏎ ")),
+	};
+	let mut events: Vec<(chrono::DateTime<chrono::Local>, Line)> = todo.status_history()
+		.iter()
+		.map(|change| (change.at(), Line::from(vec![
+			Span::styled("status ", Style::default().fg(Color::Yellow)),
+			Span::styled(change.status.label(), change.status.style()),
+		])))
+		.collect();
+	events.extend(todo.change_log().iter().map(|change| (change.at(), Line::from(vec![
+		Span::styled(format!("{} ", change.field()), Style::default().fg(Color::Yellow)),
+		Span::styled(single_line(change.old()), Style::default().fg(Color::DarkGray)),
+		Span::raw(" → "),
+		Span::raw(single_line(change.new_value())),
+	]))));
+	events.push((todo.creation_date(), Line::styled("created", Style::default().fg(Color::Yellow))));
+	// stable, so changes made at the same second keep the order they were made in
+	events.sort_by_key(|(at, _)| std::cmp::Reverse(*at));
+	events.into_iter()
+		.map(|(at, line)| {
+			let mut spans = vec![Span::styled(format!("{}  ", at.format("%d-%m-%Y %H:%M")), Style::default().fg(Color::Gray))];
+			spans.extend(line.spans);
+			Line::from(spans)
+		})
+		.collect()
+}
+
 pub fn leave() {}
 
 
@@ -0,0 +1,296 @@
+use std::{collections::HashSet, path::Path};
+use chrono::Utc;
+use serde::{Serialize, Deserialize};
+use serde_json;
+
+use crate::archive::Archive;
+use crate::schema;
+use crate::settings::Settings;
+use crate::todo::{renumber_taken, IdCounter, TodoId, TodoList};
+use crate::trash::Trash;
+use crate::storage::write_atomically;
+
+// every named list of the data file, only one of them is shown at a time
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Workspace {
+	lists: Vec<TodoList>,
+	#[serde(default)]
+	active: usize,
+	#[serde(default)]
+	settings: Settings,
+	#[serde(default)]
+	trash: Trash,
+	// files from before it was kept numbered each list on its own, loading makes their ids unique
+	#[serde(default)]
+	next_id: IdCounter,
+}
+
+impl Default for Workspace {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Workspace {
+	pub fn new() -> Self {
+		Workspace {
+			lists: vec![TodoList::new()],
+			active: 0,
+			settings: Settings::default(),
+			trash: Trash::new(),
+			next_id: IdCounter::default(),
+		}
+	}
+
+	pub fn lists(&self) -> &[TodoList] {
+		&self.lists
+	}
+
+	pub fn settings(&self) -> &Settings {
+		&self.settings
+	}
+
+	pub fn settings_mut(&mut self) -> &mut Settings {
+		&mut self.settings
+	}
+
+	pub fn trash(&self) -> &Trash {
+		&self.trash
+	}
+
+	pub fn trash_mut(&mut self) -> &mut Trash {
+		&mut self.trash
+	}
+
+	pub fn active_index(&self) -> usize {
+		self.active
+	}
+
+	pub fn active_list(&self) -> &TodoList {
+		&self.lists[self.active]
+	}
+
+	pub fn active_list_mut(&mut self) -> &mut TodoList {
+		&mut self.lists[self.active]
+	}
+
+	pub fn list_mut(&mut self, idx: usize) -> Option<&mut TodoList> {
+		self.lists.get_mut(idx)
+	}
+
+	// adding todos or closing a recurring one takes new ids
+	pub fn active_list_and_ids(&mut self) -> (&mut TodoList, &mut IdCounter) {
+		(&mut self.lists[self.active], &mut self.next_id)
+	}
+
+	pub fn list_and_ids(&mut self, idx: usize) -> Option<(&mut TodoList, &mut IdCounter)> {
+		self.lists.get_mut(idx).map(|list| (list, &mut self.next_id))
+	}
+
+	// swaps in lists kept from before, like when undoing. the counter is not part of them and never goes back
+	pub fn replace_lists(&mut self, lists: Vec<TodoList>, active: usize) {
+		self.lists = lists;
+		if self.lists.is_empty() {
+			self.lists.push(TodoList::new());
+		}
+		self.active = active.min(self.lists.len() - 1);
+		self.backfill_ids();
+	}
+
+	// makes every id unique across the lists and the trash, a later todo that shares one gets a new id
+	fn backfill_ids(&mut self) -> HashSet<TodoId> {
+		let max_id = self.lists.iter().map(TodoList::max_id)
+			.chain(self.trash.entries().iter().map(|entry| TodoList::max_id_in(std::slice::from_ref(entry.todo()))))
+			.max()
+			.unwrap_or(0);
+		self.next_id.skip_past(max_id);
+		let mut taken = HashSet::new();
+		for list in self.lists.iter_mut() {
+			list.backfill_ids(&mut taken, &mut self.next_id);
+		}
+		for entry in self.trash.entries_mut() {
+			renumber_taken(std::slice::from_mut(entry.todo_mut()), &mut taken, &mut self.next_id);
+		}
+		taken
+	}
+
+	pub fn continue_ids(&mut self, other: &Workspace) {
+		self.next_id.catch_up(other.next_id);
+	}
+
+	// archived todos can't share an id with the todos of the workspace either, it is kept in its own file
+	pub fn adopt_archive(&mut self, archive: &mut Archive) {
+		for entry in archive.entries() {
+			self.next_id.skip_past(TodoList::max_id_in(std::slice::from_ref(entry.todo())));
+		}
+		let mut taken = self.backfill_ids();
+		for entry in archive.entries_mut() {
+			renumber_taken(std::slice::from_mut(entry.todo_mut()), &mut taken, &mut self.next_id);
+		}
+	}
+
+	pub fn set_active(&mut self, idx: usize) {
+		if idx < self.lists.len() {
+			self.active = idx;
+		}
+	}
+
+	pub fn position_of(&self, name: &str) -> Option<usize> {
+		self.lists.iter().position(|list| list.name().eq_ignore_ascii_case(name))
+	}
+
+	// returns the index of the new list, or None when the name is empty or already used
+	pub fn add_list(&mut self, name: &str) -> Option<usize> {
+		let name = name.trim();
+		if name.is_empty() || self.position_of(name).is_some() {
+			return None;
+		}
+		self.lists.push(TodoList::with_name(name.to_string()));
+		Some(self.lists.len() - 1)
+	}
+
+	pub fn rename_active(&mut self, name: &str) -> bool {
+		let name = name.trim();
+		match self.position_of(name) {
+			_ if name.is_empty() => false,
+			Some(idx) if idx != self.active => false,
+			_ => {
+				self.active_list_mut().set_name(name.to_string());
+				true
+			}
+		}
+	}
+
+	// the last list can't be removed, there always has to be one to show
+	pub fn remove_active(&mut self) -> Option<TodoList> {
+		if self.lists.len() <= 1 {
+			return None;
+		}
+		let removed = self.lists.remove(self.active);
+		self.active = self.active.min(self.lists.len() - 1);
+		Some(removed)
+	}
+
+	// list index and id of the todo whose clock is running, there is at most one
+	pub fn tracked_todo(&self) -> Option<(usize, TodoId)> {
+		self.lists.iter().enumerate().find_map(|(idx, list)| list.tracked_todo().map(|todo| (idx, todo.id())))
+	}
+
+	pub fn stop_tracking(&mut self) -> bool {
+		let mut stopped = false;
+		for list in self.lists.iter_mut() {
+			stopped |= list.stop_tracking();
+		}
+		stopped
+	}
+
+	// starts the clock on a todo of the active list, stopping it everywhere else
+	pub fn start_tracking(&mut self, id: TodoId) -> bool {
+		self.stop_tracking();
+		self.active_list_mut().start_tracking(id)
+	}
+
+	pub fn save_to_file(&self, filename: &Path) -> std::io::Result<()> {
+		let json = schema::to_versioned_json(self)?;
+		write_atomically(filename, json.as_bytes())
+	}
+
+	// older files are migrated to the current schema, see schema::read_migrated
+	pub fn load_from_file(filename: &Path) -> std::io::Result<Self> {
+		Self::from_document(schema::read_migrated(filename, schema::Kind::Workspace)?)
+	}
+
+	// a file that is only looked at, like a backup, is migrated without copying it anywhere
+	pub fn read_from_file(filename: &Path) -> std::io::Result<Self> {
+		let (document, _) = schema::read_document(filename, schema::Kind::Workspace)?;
+		Self::from_document(document)
+	}
+
+	fn from_document(document: serde_json::Value) -> std::io::Result<Self> {
+		let mut workspace: Workspace = serde_json::from_value(document)?;
+		if workspace.lists.is_empty() {
+			workspace.lists.push(TodoList::new());
+		}
+		workspace.active = workspace.active.min(workspace.lists.len() - 1);
+		workspace.backfill_ids();
+		for list in workspace.lists.iter_mut() {
+			list.refresh_links();
+		}
+		let retention = chrono::Duration::days(workspace.settings.trash_retention_days.into());
+		workspace.trash.purge_deleted_before(Utc::now() - retention);
+		Ok(workspace)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	fn todo(id: TodoId, title: &str, blocked_by: &[TodoId]) -> serde_json::Value {
+		json!({ "id": id, "title": title, "body": null, "creation_date": 1700000000, "last_edit_date": 1700000000, "blocked_by": blocked_by })
+	}
+
+	fn ids_of(list: &TodoList) -> Vec<TodoId> {
+		list.walk().iter().map(|todo| todo.id()).collect()
+	}
+
+	// files from before the counter numbered each list from 1
+	fn legacy_workspace() -> Workspace {
+		serde_json::from_value(json!({
+			"lists": [
+				{ "name": "Work", "next_id": 3, "todos": [todo(1, "report", &[2]), todo(2, "data", &[])] },
+				{ "name": "Home", "next_id": 3, "todos": [todo(1, "dishes", &[2]), todo(2, "soap", &[])], "selected": 2 },
+			],
+		})).unwrap()
+	}
+
+	#[test]
+	fn ids_shared_between_lists_are_made_unique() {
+		let mut workspace = legacy_workspace();
+		workspace.backfill_ids();
+		let work = ids_of(&workspace.lists()[0]);
+		let home = ids_of(&workspace.lists()[1]);
+		assert_eq!(work, [1, 2]);
+		assert!(home.iter().all(|id| !work.contains(id)));
+		// the blocker and the selection follow the todo they meant
+		let home_list = &workspace.lists()[1];
+		let soap = home_list.walk().into_iter().find(|todo| todo.title() == "soap").unwrap().id();
+		let dishes = home_list.walk().into_iter().find(|todo| todo.title() == "dishes").unwrap();
+		assert_eq!(dishes.blocked_by().copied().collect::<Vec<_>>(), [soap]);
+		assert_eq!(home_list.selected(), Some(soap));
+		// new todos take ids no list has
+		let (list, ids) = workspace.active_list_and_ids();
+		let new_id = list.add_todo(ids, String::from("new"), None);
+		assert!(!work.contains(&new_id) && !home.contains(&new_id));
+	}
+
+	#[test]
+	fn moving_a_todo_keeps_its_id() {
+		let mut workspace = Workspace::new();
+		let home = workspace.add_list("Home").unwrap();
+		let (list, ids) = workspace.active_list_and_ids();
+		let id = list.add_todo(ids, String::from("dishes"), None);
+		let (list, ids) = workspace.list_and_ids(home).unwrap();
+		let other = list.add_todo(ids, String::from("laundry"), None);
+		assert_ne!(id, other);
+		let todo = workspace.active_list_mut().remove_todo(id).unwrap();
+		let (list, ids) = workspace.list_and_ids(home).unwrap();
+		assert_eq!(list.insert_todo(ids, todo), id);
+		assert_eq!(ids_of(&workspace.lists()[home]), [id, other]);
+	}
+
+	#[test]
+	fn archived_todos_keep_ids_apart_from_the_lists() {
+		let mut workspace = legacy_workspace();
+		let mut archive: Archive = serde_json::from_value(json!({
+			"entries": [{ "list": "Work", "archived_at": 1700000000, "todo": todo(1, "old report", &[]) }],
+		})).unwrap();
+		workspace.adopt_archive(&mut archive);
+		let archived = archive.entries()[0].todo().id();
+		assert!(workspace.lists().iter().all(|list| list.get_todo(archived).is_none()));
+		let (list, ids) = workspace.active_list_and_ids();
+		let new_id = list.add_todo(ids, String::from("new"), None);
+		assert_ne!(new_id, archived);
+	}
+}